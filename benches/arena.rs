@@ -0,0 +1,92 @@
+// Compares free-list strategies of `Arena` against the original sorted-deque implementation.
+// $> cargo bench --bench arena
+
+use containers::{Arena, FreePolicy};
+use std::{collections::VecDeque, hint::black_box, time::Instant};
+
+const OBJECTS: usize = 100_000;
+const ROUNDS: usize = 10;
+
+/// Original `Arena` free list: sorted `VecDeque` with binary-search insert.
+struct SortedArena<T> {
+    storage: Vec<Option<T>>,
+    free_indices: VecDeque<usize>,
+}
+
+impl<T> SortedArena<T> {
+    fn new(initial_size: usize) -> Self {
+        Self {
+            storage: (0..initial_size).map(|_| None).collect(),
+            free_indices: (0..initial_size).collect(),
+        }
+    }
+
+    fn allocate(&mut self, value: T) -> usize {
+        if self.free_indices.is_empty() {
+            let old_size = self.storage.len();
+            let new_size = (old_size * 2).max(1);
+            self.storage.resize_with(new_size, || None);
+            self.free_indices.extend(old_size..new_size);
+        }
+        let index = self.free_indices.pop_front().unwrap();
+        self.storage[index] = Some(value);
+        index
+    }
+
+    fn free(&mut self, index: usize) {
+        if self.storage[index].take().is_some() {
+            match self.free_indices.binary_search(&index) {
+                Ok(_) => unreachable!(),
+                Err(pos) => self.free_indices.insert(pos, index),
+            }
+        }
+    }
+}
+
+/// Scattered (but deterministic) teardown order, like objects dying in a frame.
+fn teardown_order() -> Vec<usize> {
+    let mut order: Vec<usize> = (0..OBJECTS).collect();
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    for i in (1..order.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        order.swap(i, state as usize % (i + 1));
+    }
+    order
+}
+
+fn bench(name: &str, mut round: impl FnMut()) {
+    round(); // warmup
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        round();
+    }
+    println!("{name:<24} {:?} / round", start.elapsed() / ROUNDS as u32);
+}
+
+fn main() {
+    let order = teardown_order();
+
+    let mut sorted = SortedArena::new(OBJECTS);
+    bench("sorted deque (original)", || {
+        for i in 0..OBJECTS {
+            black_box(sorted.allocate(i));
+        }
+        for &index in &order {
+            sorted.free(index);
+        }
+    });
+
+    for policy in [FreePolicy::Lifo, FreePolicy::LowestFirst] {
+        let mut arena = Arena::with_policy(OBJECTS, policy);
+        bench(&format!("{policy:?}"), || {
+            for i in 0..OBJECTS {
+                black_box(arena.allocate(i));
+            }
+            for &index in &order {
+                arena.free(index);
+            }
+        });
+    }
+}
@@ -1,28 +1,263 @@
-use std::collections::VecDeque;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
 
-#[derive(Default)]
+/// Generation-checked reference to a slot of an [`Arena`].
+///
+/// Unlike a bare `usize`, a handle stops resolving once its slot is freed,
+/// even if the slot gets reused by a later allocation.
+/// Typed by element, so a `Handle<Mesh>` can't index an `Arena<Material>`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct Handle<T> {
+    index: usize,
+    generation: u32,
+    // fn() -> T keeps Handle Send + Sync regardless of T
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(index: usize, generation: u32) -> Self {
+        Self {
+            index,
+            generation,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Slot index this handle points to (same value the `usize` API uses).
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Generation of the slot at the time the handle was created.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// manual impls, derives would require the same traits from T
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index, self.generation).cmp(&(other.index, other.generation))
+    }
+}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// Order in which freed slots get reused.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FreePolicy {
+    /// Most recently freed slot is reused first. O(1) `free`.
+    #[default]
+    Lifo,
+    /// Lowest free index is reused first, keeping live objects packed at the front. O(log n) `free`.
+    LowestFirst,
+}
+
+/// Terminates the intrusive free list.
+const NO_FREE: usize = usize::MAX;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Slot<T> {
+    Occupied(T),
+    /// Free slot, storing index of the next free slot (intrusive free list).
+    Vacant(usize),
+}
+
+impl<T> Slot<T> {
+    fn as_ref(&self) -> Option<&T> {
+        match self {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }
+    }
+
+    fn as_mut(&mut self) -> Option<&mut T> {
+        match self {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }
+    }
+
+    fn into_option(self) -> Option<T> {
+        match self {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }
+    }
+
+    fn is_occupied(&self) -> bool {
+        matches!(self, Slot::Occupied(_))
+    }
+}
+
+/// With the `serde` feature, (de)serializes holes, free list and generations as is,
+/// so indices and handles taken before serialization stay valid after a round-trip.
+/// Loading checks that lengths, free list and `len` are consistent, and fails otherwise.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ArenaData<T>"))]
 pub struct Arena<T> {
-    // Actual elements, with `None` representing free slots.
-    storage: Vec<Option<T>>,
-    /// Sorted deque of indices.
-    free_indices: VecDeque<usize>, // Keeps track of available slots
+    // Actual elements, with `Vacant` slots linking into the free list.
+    storage: Vec<Slot<T>>,
+    /// Per-slot generation, bumped every time the slot is freed.
+    generations: Vec<u32>,
+    /// Head of the intrusive free list (`FreePolicy::Lifo`).
+    free_head: usize,
+    /// Min-heap of free indices (`FreePolicy::LowestFirst`).
+    free_heap: BinaryHeap<Reverse<usize>>,
+    policy: FreePolicy,
+    /// Number of live objects.
+    len: usize,
+}
+
+/// Arena fields as they were serialized, validated before becoming an `Arena`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ArenaData<T> {
+    storage: Vec<Slot<T>>,
+    generations: Vec<u32>,
+    free_head: usize,
+    free_heap: BinaryHeap<Reverse<usize>>,
+    policy: FreePolicy,
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<ArenaData<T>> for Arena<T> {
+    type Error = &'static str;
+
+    fn try_from(data: ArenaData<T>) -> Result<Self, Self::Error> {
+        let slots = data.storage.len();
+        // generations may outlive trimmed slots (see `shrink_to_fit`), never the other way around
+        if data.generations.len() < slots {
+            return Err("arena has fewer generations than slots");
+        }
+        let live = data.storage.iter().filter(|slot| slot.is_occupied()).count();
+        if data.len != live {
+            return Err("arena len does not match its occupied slots");
+        }
+        // every vacant slot must be in the free list exactly once
+        let mut listed = vec![false; slots];
+        let mut list = |index: usize| match data.storage.get(index) {
+            Some(Slot::Vacant(_)) if !listed[index] => {
+                listed[index] = true;
+                Ok(())
+            }
+            _ => Err("arena free list is corrupt"),
+        };
+        match data.policy {
+            FreePolicy::Lifo => {
+                let mut index = data.free_head;
+                while index != NO_FREE {
+                    list(index)?;
+                    let Slot::Vacant(next) = data.storage[index] else {
+                        unreachable!()
+                    };
+                    index = next;
+                }
+            }
+            FreePolicy::LowestFirst => {
+                for &Reverse(index) in &data.free_heap {
+                    list(index)?;
+                }
+            }
+        }
+        if listed.iter().filter(|&&listed| listed).count() != slots - live {
+            return Err("arena free list misses vacant slots");
+        }
+        Ok(Self {
+            storage: data.storage,
+            generations: data.generations,
+            free_head: data.free_head,
+            free_heap: data.free_heap,
+            policy: data.policy,
+            len: data.len,
+        })
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
 }
 
 impl<T> Arena<T> {
     /// Creates an arena with a given initial size.
     pub fn new(initial_size: usize) -> Self {
-        let free_indices = (0..initial_size).collect();
-        let storage = (0..initial_size).map(|_| None).collect();
-        Self {
-            storage,
-            free_indices,
-        }
+        Self::with_policy(initial_size, FreePolicy::default())
+    }
+
+    /// Creates an arena with a given initial size and slot reuse policy.
+    pub fn with_policy(initial_size: usize, policy: FreePolicy) -> Self {
+        let mut arena = Self {
+            storage: Vec::new(),
+            generations: Vec::new(),
+            free_head: NO_FREE,
+            free_heap: BinaryHeap::new(),
+            policy,
+            len: 0,
+        };
+        arena.grow(initial_size);
+        arena
+    }
+
+    /// Returns the slot reuse policy of this arena.
+    pub fn policy(&self) -> FreePolicy {
+        self.policy
     }
 
     /// Allocates a new object in the arena, returning a handle.
     pub fn allocate(&mut self, value: T) -> Option<usize> {
-        if let Some(index) = self.free_indices.pop_front() {
-            self.storage[index] = Some(value);
+        self.len += 1;
+        if let Some(index) = self.pop_free() {
+            self.storage[index] = Slot::Occupied(value);
             Some(index)
         } else {
             let grow_to = if self.storage.is_empty() {
@@ -31,8 +266,8 @@ impl<T> Arena<T> {
                 self.storage.len() * 2
             };
             self.grow(grow_to);
-            if let Some(index) = self.free_indices.pop_front() {
-                self.storage[index] = Some(value);
+            if let Some(index) = self.pop_free() {
+                self.storage[index] = Slot::Occupied(value);
                 Some(index)
             } else {
                 unreachable!()
@@ -49,43 +284,426 @@ impl<T> Arena<T> {
     }
 
     pub fn take(&mut self, index: usize) -> Option<T> {
-        let temp = self.storage[index].take()?;
-        self.free(index);
-        Some(temp)
+        self.try_take(index).ok()
     }
 
     pub fn free(&mut self, index: usize) {
-        if index < self.storage.len() && self.storage[index].is_some() {
-            self.storage[index] = None;
-            match self.free_indices.binary_search(&index) {
-                Ok(_) => unreachable!(),
-                Err(pos) => self.free_indices.insert(pos, index),
+        if index < self.storage.len() && self.storage[index].is_occupied() {
+            self.release(index);
+        }
+    }
+
+    /// Unlinks a slot from the free list. Does not touch the slot itself.
+    fn pop_free(&mut self) -> Option<usize> {
+        match self.policy {
+            FreePolicy::Lifo => {
+                let index = self.free_head;
+                if index == NO_FREE {
+                    return None;
+                }
+                self.free_head = match self.storage[index] {
+                    Slot::Vacant(next) => next,
+                    Slot::Occupied(_) => unreachable!(),
+                };
+                Some(index)
+            }
+            FreePolicy::LowestFirst => self.free_heap.pop().map(|Reverse(index)| index),
+        }
+    }
+
+    /// Returns slot to the free list and invalidates handles pointing to it.
+    /// Slot must be occupied.
+    fn release(&mut self, index: usize) -> T {
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.len -= 1;
+        match std::mem::replace(&mut self.storage[index], Slot::Vacant(NO_FREE)) {
+            Slot::Occupied(value) => {
+                self.push_free(index);
+                value
+            }
+            Slot::Vacant(_) => unreachable!(),
+        }
+    }
+
+    /// Links a vacant slot into the free list.
+    /// Slot must not be in the free list already.
+    fn push_free(&mut self, index: usize) {
+        match self.policy {
+            FreePolicy::Lifo => {
+                self.storage[index] = Slot::Vacant(self.free_head);
+                self.free_head = index;
+            }
+            FreePolicy::LowestFirst => {
+                self.storage[index] = Slot::Vacant(NO_FREE);
+                self.free_heap.push(Reverse(index));
+            }
+        }
+    }
+
+    /// Links every slot in `range` into the free list, lowest index popped first.
+    /// Slots must not be in the free list already.
+    fn link_free(&mut self, range: std::ops::Range<usize>) {
+        for index in range.rev() {
+            self.push_free(index);
+        }
+    }
+
+    /// Relinks the free list from scratch, after slots were moved or trimmed.
+    fn rebuild_free_list(&mut self) {
+        self.free_head = NO_FREE;
+        self.free_heap.clear();
+        for index in (0..self.storage.len()).rev() {
+            if !self.storage[index].is_occupied() {
+                self.push_free(index);
             }
         }
     }
 
     pub fn clear(&mut self) {
-        self.storage.iter_mut().for_each(|slot| *slot = None);
-        self.free_indices.clear();
-        self.free_indices.extend(0..self.storage.len());
+        for (slot, generation) in self.storage.iter_mut().zip(self.generations.iter_mut()) {
+            if slot.is_occupied() {
+                *generation = generation.wrapping_add(1);
+            }
+        }
+        self.len = 0;
+        self.free_head = NO_FREE;
+        self.free_heap.clear();
+        self.link_free(0..self.storage.len());
     }
 
     pub fn grow(&mut self, new_size: usize) {
         let old_size = self.storage.len();
         if new_size > old_size {
-            self.storage.resize_with(new_size, || None);
-            self.free_indices.extend(old_size..new_size);
+            self.storage.resize_with(new_size, || Slot::Vacant(NO_FREE));
+            // generations outlive trimmed slots (see `shrink_to_fit`), never reset them
+            if self.generations.len() < new_size {
+                self.generations.resize(new_size, 0);
+            }
+            self.link_free(old_size..new_size);
         }
     }
 
     pub fn total_size(&self) -> usize {
         self.storage.len()
     }
+
+    /// Number of live objects.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of slots, live or free. Allocating past it grows the arena.
+    pub fn capacity(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Number of free slots.
+    pub fn vacant_len(&self) -> usize {
+        self.storage.len() - self.len
+    }
+
+    /// Makes sure at least `additional` objects can be allocated without growing.
+    pub fn reserve(&mut self, additional: usize) {
+        let vacant = self.vacant_len();
+        if additional > vacant {
+            self.grow(self.storage.len() + additional - vacant);
+        }
+    }
+
+    /// Trims free slots past the last live object and releases unused memory.
+    /// Live indices and handles stay valid.
+    pub fn shrink_to_fit(&mut self) {
+        let new_size = self
+            .storage
+            .iter()
+            .rposition(Slot::is_occupied)
+            .map_or(0, |last| last + 1);
+        if new_size < self.storage.len() {
+            self.storage.truncate(new_size);
+            // generations of trimmed slots are kept, so regrowing can't revive stale handles
+            self.rebuild_free_list();
+        }
+        self.storage.shrink_to_fit();
+    }
+
+    /// Moves live objects down to fill every hole, then trims the arena to `len()` slots.
+    /// Returns table mapping old index to new index (`None` for slots that were free),
+    /// use it to fix up external references. Handles to moved objects become stale,
+    /// get fresh ones with `handle_of`.
+    pub fn compact(&mut self) -> Vec<Option<usize>> {
+        let mut remap = vec![None; self.storage.len()];
+        let mut next = 0;
+        for (index, new_index) in remap.iter_mut().enumerate() {
+            if !self.storage[index].is_occupied() {
+                continue;
+            }
+            if index != next {
+                self.storage.swap(index, next);
+                self.generations[index] = self.generations[index].wrapping_add(1);
+            }
+            *new_index = Some(next);
+            next += 1;
+        }
+        self.storage.truncate(next);
+        self.storage.shrink_to_fit();
+        self.rebuild_free_list();
+        remap
+    }
+}
+
+// bulk removal
+impl<T> Arena<T> {
+    /// Keeps only objects for which `keep(index, &mut value)` returns `true`.
+    /// Free list is rebuilt once at the end.
+    pub fn retain(&mut self, mut keep: impl FnMut(usize, &mut T) -> bool) {
+        for (index, (slot, generation)) in self
+            .storage
+            .iter_mut()
+            .zip(self.generations.iter_mut())
+            .enumerate()
+        {
+            if let Slot::Occupied(value) = slot
+                && !keep(index, value)
+            {
+                *slot = Slot::Vacant(NO_FREE);
+                *generation = generation.wrapping_add(1);
+                self.len -= 1;
+            }
+        }
+        self.rebuild_free_list();
+    }
+
+    /// Removes every object, yielding (`index`, `T`) in index order.
+    /// Arena is left empty but keeps its capacity, even if the iterator is dropped early.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain {
+            arena: self,
+            index: 0,
+        }
+    }
+
+    /// Lazily removes and yields (`index`, `T`) of objects for which `pred(index, &mut value)` returns `true`.
+    /// Objects not reached before the iterator is dropped stay in the arena.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(usize, &mut T) -> bool,
+    {
+        ExtractIf {
+            arena: self,
+            index: 0,
+            pred,
+        }
+    }
+}
+
+// generation-checked API, for references that may outlive the object they point to
+impl<T> Arena<T> {
+    /// Allocates a new object in the arena, returning a generation-checked handle.
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        let index = self.allocate(value).unwrap();
+        Handle::new(index, self.generations[index])
+    }
+
+    /// Creates a handle for the live object at `index`.
+    pub fn handle_of(&self, index: usize) -> Option<Handle<T>> {
+        self.storage.get(index)?.as_ref()?;
+        Some(Handle::new(index, self.generations[index]))
+    }
+
+    /// Returns `true` if `handle` still points to the object it was created for.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get_handle(handle).is_some()
+    }
+
+    pub fn get_handle(&self, handle: Handle<T>) -> Option<&T> {
+        if self.generations.get(handle.index) != Some(&handle.generation) {
+            return None;
+        }
+        self.storage.get(handle.index)?.as_ref()
+    }
+
+    pub fn get_handle_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        if self.generations.get(handle.index) != Some(&handle.generation) {
+            return None;
+        }
+        self.storage.get_mut(handle.index)?.as_mut()
+    }
+
+    /// Removes the object `handle` points to. Stale handles are rejected with `None`.
+    pub fn take_handle(&mut self, handle: Handle<T>) -> Option<T> {
+        self.try_take_handle(handle).ok()
+    }
+
+    /// Frees the slot `handle` points to, returning `false` for stale handles.
+    pub fn free_handle(&mut self, handle: Handle<T>) -> bool {
+        self.take_handle(handle).is_some()
+    }
+}
+
+/// Reason a fallible arena access was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArenaError {
+    /// Index past the end of the arena.
+    OutOfBounds,
+    /// Slot holds no object.
+    Vacant,
+    /// Handle outlived its object (slot was freed, and maybe reused since).
+    StaleGeneration,
+    /// Same index requested more than once.
+    Overlapping,
+}
+
+impl fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ArenaError::OutOfBounds => "arena index out of bounds",
+            ArenaError::Vacant => "arena slot is vacant",
+            ArenaError::StaleGeneration => "arena handle is stale",
+            ArenaError::Overlapping => "arena indices overlap",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for ArenaError {}
+
+// fallible API, never panics
+impl<T> Arena<T> {
+    fn check_index(&self, index: usize) -> Result<(), ArenaError> {
+        match self.storage.get(index) {
+            None => Err(ArenaError::OutOfBounds),
+            Some(Slot::Vacant(_)) => Err(ArenaError::Vacant),
+            Some(Slot::Occupied(_)) => Ok(()),
+        }
+    }
+
+    fn check_handle(&self, handle: Handle<T>) -> Result<(), ArenaError> {
+        if handle.index >= self.storage.len() {
+            return Err(ArenaError::OutOfBounds);
+        }
+        // freeing bumps the generation, so vacant slots report stale handles
+        if self.generations[handle.index] != handle.generation {
+            return Err(ArenaError::StaleGeneration);
+        }
+        self.check_index(handle.index)
+    }
+
+    pub fn try_take(&mut self, index: usize) -> Result<T, ArenaError> {
+        self.check_index(index)?;
+        Ok(self.release(index))
+    }
+
+    pub fn try_free(&mut self, index: usize) -> Result<(), ArenaError> {
+        self.try_take(index).map(drop)
+    }
+
+    pub fn try_take_handle(&mut self, handle: Handle<T>) -> Result<T, ArenaError> {
+        self.check_handle(handle)?;
+        Ok(self.release(handle.index))
+    }
+
+    pub fn try_free_handle(&mut self, handle: Handle<T>) -> Result<(), ArenaError> {
+        self.try_take_handle(handle).map(drop)
+    }
+
+    /// Mutably borrows several live objects at once. Indices must be distinct.
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        indices: [usize; N],
+    ) -> Result<[&mut T; N], ArenaError> {
+        for (i, &index) in indices.iter().enumerate() {
+            self.check_index(index)?;
+            if indices[..i].contains(&index) {
+                return Err(ArenaError::Overlapping);
+            }
+        }
+        let slots = self
+            .storage
+            .get_disjoint_mut(indices)
+            .map_err(|_| ArenaError::Overlapping)?;
+        Ok(slots.map(|slot| slot.as_mut().unwrap()))
+    }
+}
+
+/// Panics if `handle` is stale.
+impl<T> Index<Handle<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &Self::Output {
+        self.get_handle(handle).expect("stale arena handle")
+    }
+}
+
+/// Panics if `handle` is stale.
+impl<T> IndexMut<Handle<T>> for Arena<T> {
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut Self::Output {
+        self.get_handle_mut(handle).expect("stale arena handle")
+    }
+}
+
+/// Draining iterator over (`index`, `T`) of live entries in the arena, see [`Arena::drain`].
+pub struct Drain<'a, T> {
+    arena: &'a mut Arena<T>,
+    index: usize,
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.arena.storage.len() {
+            let current = self.index;
+            self.index += 1;
+            if self.arena.storage[current].is_occupied() {
+                return Some((current, self.arena.release(current)));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+        // freed slots were pushed in index order, relink so lowest index is reused first again
+        self.arena.rebuild_free_list();
+    }
+}
+
+/// Lazy filtering iterator removing (`index`, `T`) from the arena, see [`Arena::extract_if`].
+pub struct ExtractIf<'a, T, F> {
+    arena: &'a mut Arena<T>,
+    index: usize,
+    pred: F,
+}
+
+impl<'a, T, F> Iterator for ExtractIf<'a, T, F>
+where
+    F: FnMut(usize, &mut T) -> bool,
+{
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.arena.storage.len() {
+            let current = self.index;
+            self.index += 1;
+            if let Slot::Occupied(value) = &mut self.arena.storage[current]
+                && (self.pred)(current, value)
+            {
+                return Some((current, self.arena.release(current)));
+            }
+        }
+        None
+    }
 }
 
 /// Immutable iterator over (`index`, `&T`) of live entries in the arena.
 pub struct Iter<'a, T> {
-    inner: std::iter::Enumerate<std::slice::Iter<'a, Option<T>>>,
+    inner: std::iter::Enumerate<std::slice::Iter<'a, Slot<T>>>,
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -103,7 +721,7 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
 /// Mutable iterator over (`index`, `&mut T`) of live entries in the arena.
 pub struct IterMut<'a, T> {
-    inner: std::iter::Enumerate<std::slice::IterMut<'a, Option<T>>>,
+    inner: std::iter::Enumerate<std::slice::IterMut<'a, Slot<T>>>,
 }
 
 impl<'a, T> Iterator for IterMut<'a, T> {
@@ -143,7 +761,7 @@ impl<'a, T> IntoIterator for &'a mut Arena<T> {
 
 /// Consuming iterator over `T` of live entries in the arena (order by index).
 pub struct IntoIter<T> {
-    inner: std::vec::IntoIter<Option<T>>,
+    inner: std::vec::IntoIter<Slot<T>>,
     idx: usize,
 }
 
@@ -154,7 +772,7 @@ impl<T> Iterator for IntoIter<T> {
         while let Some(opt) = self.inner.next() {
             let current = self.idx;
             self.idx += 1;
-            if let Some(val) = opt {
+            if let Some(val) = opt.into_option() {
                 return Some((current, val));
             }
         }
@@ -173,3 +791,281 @@ impl<T> IntoIterator for Arena<T> {
         }
     }
 }
+
+/// Shows live entries as `index: value`.
+impl<T: Debug> Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+/// Allocates every value, indices follow iteration order.
+impl<T> FromIterator<T> for Arena<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut arena = Self::default();
+        arena.extend(iter);
+        arena
+    }
+}
+
+impl<T> Extend<T> for Arena<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.allocate(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_and_clone() {
+        let mut arena: Arena<_> = (0..4).collect();
+        assert_eq!(arena.get(3), Some(&3));
+        arena.free(1);
+        arena.extend([10, 11]);
+        assert_eq!(arena.get(1), Some(&10));
+        assert_eq!(arena.len(), 5);
+
+        let copy = arena.clone();
+        arena.free(0);
+        assert_eq!(copy.get(0), Some(&0));
+        assert_eq!(format!("{copy:?}"), "{0: 0, 1: 10, 2: 2, 3: 3, 4: 11}");
+    }
+
+    #[test]
+    fn test_stale_handle_rejected() {
+        let mut arena = Arena::new(0);
+        let a = arena.insert(1);
+        assert_eq!(arena.get_handle(a), Some(&1));
+
+        assert!(arena.free_handle(a));
+        // slot gets reused, but old handle must not alias the new object
+        let b = arena.insert(2);
+        assert_eq!(a.index(), b.index());
+        assert_eq!(arena.get_handle(a), None);
+        assert_eq!(arena.take_handle(a), None);
+        assert!(!arena.free_handle(a));
+        assert_eq!(arena.get_handle(b), Some(&2));
+
+        // usize API keeps working alongside
+        assert_eq!(arena.get(b.index()), Some(&2));
+        assert_eq!(arena.take(b.index()), Some(2));
+        assert!(!arena.contains(b));
+    }
+
+    #[test]
+    fn test_typed_handle_indexing() {
+        let mut arena = Arena::new(4);
+        let h = arena.insert(String::from("mesh"));
+        arena[h].push('!');
+        assert_eq!(arena[h], "mesh!");
+
+        let copy = h;
+        assert_eq!(copy, h);
+    }
+
+    #[test]
+    fn test_free_policies() {
+        let mut lifo = Arena::new(4);
+        let mut lowest = Arena::with_policy(4, FreePolicy::LowestFirst);
+        for arena in [&mut lifo, &mut lowest] {
+            for i in 0..4 {
+                assert_eq!(arena.allocate(i), Some(i));
+            }
+            arena.free(1);
+            arena.free(3);
+            arena.free(0);
+        }
+        assert_eq!(lifo.allocate(10), Some(0));
+        assert_eq!(lifo.allocate(11), Some(3));
+        assert_eq!(lifo.allocate(12), Some(1));
+        assert_eq!(lowest.allocate(10), Some(0));
+        assert_eq!(lowest.allocate(11), Some(1));
+        assert_eq!(lowest.allocate(12), Some(3));
+
+        // both grow once full
+        assert_eq!(lifo.allocate(13), Some(4));
+        assert_eq!(lowest.allocate(13), Some(4));
+        assert_eq!(lifo.total_size(), 8);
+    }
+
+    #[test]
+    fn test_len_and_compact() {
+        let mut arena = Arena::new(0);
+        let handles: Vec<_> = (0..6).map(|i| arena.insert(i)).collect();
+        assert_eq!(arena.len(), 6);
+        assert_eq!(arena.capacity(), 8);
+
+        arena.free(1);
+        arena.free(4);
+        arena.free(5);
+        assert_eq!(arena.len(), 3);
+        assert_eq!(arena.vacant_len(), 5);
+
+        arena.shrink_to_fit();
+        assert_eq!(arena.capacity(), 4);
+        assert_eq!(arena.get(3), Some(&3));
+        assert!(arena.contains(handles[3]));
+
+        // regrowing into trimmed slots must not revive stale handles
+        arena.reserve(4);
+        assert!(arena.capacity() >= 7);
+        while arena.len() < 7 {
+            arena.allocate(-1);
+        }
+        assert!(!arena.contains(handles[5]));
+
+        arena.free(0);
+        arena.free(2);
+        let remap = arena.compact();
+        assert_eq!(arena.capacity(), 5);
+        assert_eq!(remap[0], None);
+        assert_eq!(remap[2], None);
+        assert_eq!(remap[3], Some(1));
+        assert_eq!(arena.get(1), Some(&3));
+        assert!(!arena.contains(handles[3]));
+        assert_eq!(arena.handle_of(1).map(|h| arena[h]), Some(3));
+    }
+
+    #[test]
+    fn test_bulk_removal() {
+        let mut arena = Arena::new(0);
+        let handles: Vec<_> = (0..10).map(|i| arena.insert(i)).collect();
+
+        arena.retain(|_, value| *value % 2 == 0);
+        assert_eq!(arena.len(), 5);
+        assert!(!arena.contains(handles[1]));
+        assert!(arena.contains(handles[2]));
+
+        let extracted: Vec<_> = arena.extract_if(|_, value| *value >= 6).collect();
+        assert_eq!(extracted, vec![(6, 6), (8, 8)]);
+        assert_eq!(arena.len(), 3);
+
+        let capacity = arena.capacity();
+        let mut drain = arena.drain();
+        assert_eq!(drain.next(), Some((0, 0)));
+        drop(drain);
+        assert!(arena.is_empty());
+        assert_eq!(arena.capacity(), capacity);
+        assert_eq!(arena.allocate(42), Some(0));
+    }
+
+    #[test]
+    fn test_fallible_api() {
+        let mut arena = Arena::new(0);
+        let a = arena.insert(1);
+        let b = arena.insert(2);
+        assert_eq!(arena.take(100), None);
+        assert_eq!(arena.try_take(100), Err(ArenaError::OutOfBounds));
+        assert_eq!(arena.try_free_handle(a), Ok(()));
+        assert_eq!(arena.try_free(a.index()), Err(ArenaError::Vacant));
+        assert_eq!(arena.try_take_handle(a), Err(ArenaError::StaleGeneration));
+
+        let c = arena.insert(3);
+        let [x, y] = arena.get_disjoint_mut([b.index(), c.index()]).unwrap();
+        std::mem::swap(x, y);
+        assert_eq!((arena[b], arena[c]), (3, 2));
+        assert_eq!(
+            arena.get_disjoint_mut([b.index(), b.index()]).err(),
+            Some(ArenaError::Overlapping)
+        );
+        assert_eq!(
+            arena.get_disjoint_mut([b.index(), 7]).err(),
+            Some(ArenaError::OutOfBounds)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut arena = Arena::new(0);
+        let handles: Vec<_> = (0..5).map(|i| arena.insert(i.to_string())).collect();
+        arena.free_handle(handles[1]);
+        arena.free_handle(handles[3]);
+        let reused = arena.insert(String::from("reused"));
+
+        let json = serde_json::to_string(&arena).unwrap();
+        let (json_handles, json_reused) = (
+            serde_json::to_string(&handles).unwrap(),
+            serde_json::to_string(&reused).unwrap(),
+        );
+        let mut loaded: Arena<String> = serde_json::from_str(&json).unwrap();
+        let handles: Vec<Handle<String>> = serde_json::from_str(&json_handles).unwrap();
+        let reused: Handle<String> = serde_json::from_str(&json_reused).unwrap();
+
+        assert_eq!(loaded.len(), 4);
+        assert_eq!(loaded[reused], "reused");
+        for i in [0, 2, 4] {
+            assert_eq!(loaded[handles[i]], i.to_string());
+        }
+        assert!(!loaded.contains(handles[1]));
+        assert!(!loaded.contains(handles[3]));
+        // free list survived too
+        assert_eq!(loaded.allocate(String::new()), arena.allocate(String::new()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_after_shrinking() {
+        let mut arena = Arena::new(0);
+        let handles: Vec<_> = (0..6).map(|i| arena.insert(i)).collect();
+        arena.free_handle(handles[4]);
+        arena.free_handle(handles[5]);
+        arena.shrink_to_fit();
+        let json = serde_json::to_string(&arena).unwrap();
+        let mut loaded: Arena<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.total_size(), 4);
+        assert_eq!(loaded[handles[3]], 3);
+        // regrown slot still knows its old generation
+        let reused = loaded.insert(9);
+        assert_eq!(reused.index(), handles[4].index());
+        assert!(!loaded.contains(handles[4]));
+
+        arena.free_handle(handles[1]);
+        let remap = arena.compact();
+        let json = serde_json::to_string(&arena).unwrap();
+        let loaded: Arena<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded.total_size(), 3);
+        for i in [0, 2, 3] {
+            let index = remap[i].unwrap();
+            assert_eq!(loaded.get(index), Some(&(i as i32)));
+            assert_eq!(loaded.handle_of(index), arena.handle_of(index));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_corrupt_state() {
+        let mut arena = Arena::new(0);
+        let handles: Vec<_> = (0..3).map(|i| arena.insert(i)).collect();
+        arena.free_handle(handles[1]);
+        let json = serde_json::to_string(&arena).unwrap();
+        assert!(serde_json::from_str::<Arena<i32>>(&json).is_ok());
+
+        let corrupt = [
+            json.replace("\"free_head\":1", "\"free_head\":7"),
+            json.replace("\"free_head\":1", "\"free_head\":0"),
+            json.replace("\"len\":2", "\"len\":3"),
+            json.replace("\"generations\":[0,", "\"generations\":["),
+            // vacant slot left out of the list
+            json.replace("\"free_head\":1", &format!("\"free_head\":{}", usize::MAX)),
+        ];
+        for payload in corrupt {
+            assert_ne!(payload, json);
+            assert!(serde_json::from_str::<Arena<i32>>(&payload).is_err(), "{payload}");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_stale_handle_index_panics() {
+        let mut arena = Arena::new(4);
+        let h = arena.insert(1u32);
+        arena.free_handle(h);
+        let _ = arena[h];
+    }
+}
@@ -5,14 +5,14 @@ use std::{
     slice::{Iter, IterMut},
 };
 
-use qvek::uuvec2;
+use qvek::{ivec2, uuvec2};
 
 // You should index into it in this order
 //  for y in 0..y_size {
 //  for x in 0..x_size {
 // Currently, Rust cannot optimize the order automatically. TODO: MIR-OPT coherence transform
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct DArray2D<T> {
     pub data: Box<[T]>,
     pub x_size: usize,
@@ -134,6 +134,65 @@ impl<T: Clone> DArray2D<T> {
     pub fn get_mut(&mut self, x: usize, y: usize) -> &mut T {
         &mut self.data[self.index_internal(x, y)]
     }
+
+    /// New `size` array where (x, y) holds old (x, y) - `offset`, and `fill` everywhere else.
+    /// `size` may be empty (unlike `new_filled`), same as `Array3D::resize`.
+    fn remapped(&self, size: uuvec2, offset: (i64, i64), fill: T) -> Self {
+        let mut result = Self {
+            data: vec![fill; size.x * size.y].into_boxed_slice(),
+            x_size: size.x,
+            y_size: size.y,
+        };
+        // overlap, in new coordinates
+        let min_x = offset.0.clamp(0, size.x as i64);
+        let max_x = (self.x_size as i64 + offset.0).clamp(min_x, size.x as i64);
+        let min_y = offset.1.clamp(0, size.y as i64);
+        let max_y = (self.y_size as i64 + offset.1).clamp(min_y, size.y as i64);
+        let width = (max_x - min_x) as usize;
+        for y in min_y..max_y {
+            let dst = y as usize * size.x + min_x as usize;
+            let src = (y - offset.1) as usize * self.x_size + (min_x - offset.0) as usize;
+            result.data[dst..dst + width].clone_from_slice(&self.data[src..src + width]);
+        }
+        result
+    }
+
+    /// Changes size, keeping content at the origin. New cells are set to `fill`.
+    pub fn resize(&mut self, x_size: usize, y_size: usize, fill: T) {
+        *self = self.remapped(uuvec2::new(x_size, y_size), (0, 0), fill);
+    }
+
+    /// Shrinks to box `min..max` (`max` exclusive), which becomes the new origin.
+    pub fn crop(&mut self, min: uuvec2, max: uuvec2) {
+        assert!(
+            min.x <= max.x && min.y <= max.y && max.x <= self.x_size && max.y <= self.y_size,
+            "Invalid crop box {min:?}..{max:?} of {:?}",
+            self.size()
+        );
+        let offset = (-(min.x as i64), -(min.y as i64));
+        // every cell is copied over, fill is never visible
+        *self = match self.data.first() {
+            Some(any) => self.remapped(max - min, offset, any.clone()),
+            // empty array, so the box is empty too
+            None => Self {
+                data: Box::new([]),
+                x_size: max.x - min.x,
+                y_size: max.y - min.y,
+            },
+        };
+    }
+
+    /// Grows by `before` cells at the low end and `after` cells at the high end of each axis, set to `fill`.
+    pub fn pad(&mut self, before: uuvec2, after: uuvec2, fill: T) {
+        let size = self.size() + before + after;
+        *self = self.remapped(size, (before.x as i64, before.y as i64), fill);
+    }
+
+    /// Moves content by `offset` (size stays), cells it left behind are set to `fill`.
+    /// Content moved past the edge is lost.
+    pub fn shift(&mut self, offset: ivec2, fill: T) {
+        *self = self.remapped(self.size(), (offset.x as i64, offset.y as i64), fill);
+    }
 }
 
 impl<T: Clone> Index<(usize, usize)> for DArray2D<T> {
@@ -285,6 +344,39 @@ mod tests {
         assert_eq!(array.iter().count(), 4);
     }
 
+    #[test]
+    fn test_resize_crop_pad_shift() {
+        let mut array = DArray2D::new_filled(3, 2, 0);
+        for y in 0..2 {
+            for x in 0..3 {
+                array[(x, y)] = x + y * 10;
+            }
+        }
+        array.resize(2, 3, 9);
+        assert_eq!(array.data.as_ref(), &[0, 1, 10, 11, 9, 9]);
+
+        array.pad(uuvec2::new(1, 0), uuvec2::new(0, 1), 7);
+        assert_eq!(array.dimensions(), (3, 4));
+        assert_eq!(*array.get(1, 1), 10);
+        assert_eq!(*array.get(0, 1), 7);
+
+        array.crop(uuvec2::new(1, 0), uuvec2::new(3, 2));
+        assert_eq!(array.data.as_ref(), &[0, 1, 10, 11]);
+
+        array.shift(ivec2::new(-1, 1), 5);
+        assert_eq!(array.data.as_ref(), &[5, 5, 1, 5]);
+
+        // empty sizes are fine, like for `Array3D`
+        array.crop(uuvec2::new(1, 1), uuvec2::new(1, 2));
+        assert_eq!(array.dimensions(), (0, 1));
+        array.crop(uuvec2::zero(), uuvec2::new(0, 0));
+        assert_eq!(array.dimensions(), (0, 0));
+        array.resize(0, 4, 1);
+        assert!(array.data.is_empty());
+        array.resize(2, 2, 1);
+        assert_eq!(array.data.as_ref(), &[1, 1, 1, 1]);
+    }
+
     #[test]
     #[should_panic]
     fn test_out_of_bounds() {
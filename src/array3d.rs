@@ -1,9 +1,12 @@
 #![allow(unused_imports)]
+use crate::array3d_iter::Positions;
+use crate::layout::{Layout, Linear};
 use qvek::vek::{self, Vec2, Vec3, Vec4};
 use std::{
     fmt::{self, Debug},
+    hash::{Hash, Hasher},
     marker::PhantomData,
-    ops::{Index, IndexMut},
+    ops::{DerefMut, Index, IndexMut},
     slice::{Iter, IterMut},
 };
 
@@ -64,7 +67,7 @@ impl Dim3 for RuntimeDims {
 // impl
 
 /// Compile-time dimensions using const generics.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct ConstDims<const X: usize, const Y: usize, const Z: usize>;
 
 impl<const X: usize, const Y: usize, const Z: usize> Dim3 for ConstDims<X, Y, Z> {
@@ -79,24 +82,111 @@ impl<const X: usize, const Y: usize, const Z: usize> Dim3 for ConstDims<X, Y, Z>
     }
 }
 
-/// Generic 3D array, parameterized by a Dim3, which allows runtime flexibility or template perfomance.
-pub struct Array3D<T, D: Dim3> {
-    pub data: Box<[T]>,
+/// Buffer passed to `Array3D::from_vec` has the wrong length for its dims and layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthMismatch {
+    /// Storage length the dims and layout need.
+    pub expected: usize,
+    /// Length of the buffer.
+    pub actual: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "array needs {} elements, buffer has {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for LengthMismatch {}
+
+/// Checks `min..max` is a valid box within `size`.
+pub(crate) fn check_region(size: Vec3<usize>, min: Vec3<usize>, max: Vec3<usize>) {
+    assert!(
+        min.x <= max.x && min.y <= max.y && min.z <= max.z,
+        "region min {min:?} is past max {max:?}"
+    );
+    assert!(
+        max.x <= size.x && max.y <= size.y && max.z <= size.z,
+        "region max {max:?} out of bounds {size:?}"
+    );
+}
+
+/// Flat element storage of an `Array3D`, in storage order.
+/// `Box<[T]>` by default, `InlineStorage` keeps elements inline (see `InlineArray3D`).
+///
+/// # Safety
+/// `deref` and `deref_mut` must return the same slice every time, as long as the storage is not mutated through other means.
+/// Views and iterators check the length once and then index through raw pointers.
+pub unsafe trait Storage<T>: DerefMut<Target = [T]> {}
+
+unsafe impl<T> Storage<T> for Box<[T]> {}
+
+/// Generic 3D array, parameterized by a Dim3, which allows runtime flexibility or template perfomance,
+/// a Layout deciding storage order (`Linear` by default, see `layout.rs`),
+/// and a Storage holding the elements (heap-allocated `Box<[T]>` by default).
+/// `data` is in storage order, and may contain padding for non-linear layouts.
+#[derive(Clone, Copy)]
+pub struct Array3D<T, D: Dim3, L: Layout = Linear, S = Box<[T]>> {
+    pub data: S,
     pub dims: D,
+    pub layout: L,
+    pub(crate) element: PhantomData<T>,
 }
 
-impl<T, D: Dim3> Array3D<T, D> {
-    /// Creates a new uninitialized array from a dimension provider and data.
-    fn from_boxed(dims: D, data: Box<[T]>) -> Self {
-        assert_eq!(dims.total_len(), data.len());
-        Self { dims, data }
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// Wraps `data` in storage order. Panics if its length does not match dims and layout.
+    pub(crate) fn from_storage(dims: D, data: S) -> Self {
+        assert_eq!(L::storage_len(&dims), data.len());
+        Self {
+            dims,
+            data,
+            layout: L::default(),
+            element: PhantomData,
+        }
+    }
+
+    /// Checks `data` is long enough for `dims`. Fields are public, so safe code can break this,
+    /// and raw-pointer views and iterators must check it before indexing storage.
+    pub(crate) fn check_storage(&self) {
+        assert!(
+            self.data.len() >= L::storage_len(&self.dims),
+            "storage of {} elements is too short for dims {:?}",
+            self.data.len(),
+            self.dimensions()
+        );
     }
 
     /// Computes flat index for (x, y, z).
-    // TODO: look into different indexing and move it into generic
+    /// Panics if out of bounds, also in release: an x past the row would otherwise alias the next row.
     pub fn index_internal(&self, x: usize, y: usize, z: usize) -> usize {
-        debug_assert!(x < self.dims.x() && y < self.dims.y() && z < self.dims.z());
-        x + y * self.dims.x() + z * self.dims.x() * self.dims.y()
+        assert!(
+            self.in_bounds(x, y, z),
+            "Index out of bounds x: {x} y: {y} z: {z}, dims: {:?}",
+            self.dimensions()
+        );
+        L::index(&self.dims, x, y, z)
+    }
+
+    /// Returns `true` if storage has slots that are not elements (see `Layout`).
+    pub fn has_padding(&self) -> bool {
+        L::storage_len(&self.dims) != self.dims.total_len()
+    }
+
+    /// Returns `true` if (x, y, z) is inside the array.
+    pub fn in_bounds(&self, x: usize, y: usize, z: usize) -> bool {
+        x < self.dims.x() && y < self.dims.y() && z < self.dims.z()
+    }
+
+    /// Flat index for signed `pos`, `None` if any coordinate is negative or out of bounds.
+    fn index_signed(&self, pos: Vec3<i32>) -> Option<usize> {
+        let x = usize::try_from(pos.x).ok()?;
+        let y = usize::try_from(pos.y).ok()?;
+        let z = usize::try_from(pos.z).ok()?;
+        self.in_bounds(x, y, z).then(|| L::index(&self.dims, x, y, z))
     }
 
     /// Returns the dimensions as a tuple.
@@ -111,7 +201,8 @@ impl<T, D: Dim3> Array3D<T, D> {
 
     /// Mutable reference at (x, y, z).
     pub fn get_mut(&mut self, x: usize, y: usize, z: usize) -> &mut T {
-        &mut self.data[self.index_internal(x, y, z)]
+        let idx = self.index_internal(x, y, z);
+        &mut self.data[idx]
     }
 
     /// Sets value at (x, y, z).
@@ -120,12 +211,36 @@ impl<T, D: Dim3> Array3D<T, D> {
         self.data[idx] = value;
     }
 
-    /// Immutable iterator over all elements.
+    /// Shared reference at (x, y, z), `None` if out of bounds.
+    pub fn try_get(&self, x: usize, y: usize, z: usize) -> Option<&T> {
+        self.in_bounds(x, y, z).then(|| &self.data[L::index(&self.dims, x, y, z)])
+    }
+
+    /// Mutable reference at (x, y, z), `None` if out of bounds.
+    pub fn try_get_mut(&mut self, x: usize, y: usize, z: usize) -> Option<&mut T> {
+        if !self.in_bounds(x, y, z) {
+            return None;
+        }
+        Some(&mut self.data[L::index(&self.dims, x, y, z)])
+    }
+
+    /// Shared reference at signed `pos`, `None` if negative or out of bounds.
+    pub fn get_signed(&self, pos: Vec3<i32>) -> Option<&T> {
+        Some(&self.data[self.index_signed(pos)?])
+    }
+
+    /// Mutable reference at signed `pos`, `None` if negative or out of bounds.
+    pub fn get_signed_mut(&mut self, pos: Vec3<i32>) -> Option<&mut T> {
+        let index = self.index_signed(pos)?;
+        Some(&mut self.data[index])
+    }
+
+    /// Immutable iterator over all elements (in storage order, including padding).
     pub fn iter(&self) -> Iter<'_, T> {
         self.data.iter()
     }
 
-    /// Mutable iterator over all elements.
+    /// Mutable iterator over all elements (in storage order, including padding).
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         self.data.iter_mut()
     }
@@ -134,14 +249,16 @@ impl<T, D: Dim3> Array3D<T, D> {
     /// # Safety
     /// slice OOB (out-of-bounds) when array OOB (so UB)
     pub unsafe fn get_unchecked(&self, x: usize, y: usize, z: usize) -> &T {
-        unsafe { self.data.get_unchecked(self.index_internal(x, y, z)) }
+        debug_assert!(self.in_bounds(x, y, z));
+        unsafe { self.data.get_unchecked(L::index(&self.dims, x, y, z)) }
     }
 
     /// Unchecked mutable reference (no bounds checks)
     /// # Safety
     /// slice OOB (out-of-bounds) when array OOB (so UB)
     pub unsafe fn get_unchecked_mut(&mut self, x: usize, y: usize, z: usize) -> &mut T {
-        unsafe { self.data.get_unchecked_mut(self.index_internal(x, y, z)) }
+        debug_assert!(self.in_bounds(x, y, z));
+        unsafe { self.data.get_unchecked_mut(L::index(&self.dims, x, y, z)) }
     }
 
     /// Unchecked setter without bounds checks
@@ -152,14 +269,16 @@ impl<T, D: Dim3> Array3D<T, D> {
     }
 }
 
-impl<T: Clone, D: Dim3> Array3D<T, D> {
-    /// Creates a new array with all elements cloned from `value`.
-    pub fn new_filled(dims: D, value: T) -> Self {
-        let len = dims.total_len();
+impl<T: Clone, D: Dim3, L: Layout> Array3D<T, D, L> {
+    /// Creates a new array in a given layout with all elements cloned from `value`.
+    pub fn new_filled_in(dims: D, _layout: L, value: T) -> Self {
+        let len = L::storage_len(&dims);
         let data = vec![value; len].into_boxed_slice();
-        Self::from_boxed(dims, data)
+        Self::from_storage(dims, data)
     }
+}
 
+impl<T: Clone, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
     /// Fills every element with `value`.
     pub fn fill(&mut self, value: T) {
         self.data.fill(value);
@@ -177,19 +296,103 @@ impl<T: Clone, D: Dim3> Array3D<T, D> {
     }
 }
 
+impl<T: Clone + Default, D: Dim3, L: Layout> Array3D<T, D, L> {
+    /// Creates array in a given layout filled with `T::default()`.
+    pub fn new_default_in(dims: D, layout: L) -> Self {
+        Self::new_filled_in(dims, layout, T::default())
+    }
+}
+
+impl<T, D: Dim3, L: Layout> Array3D<T, D, L> {
+    /// Creates array in a given layout via a generator function.
+    pub fn from_fn_in<F: Fn() -> T>(dims: D, _layout: L, generator: F) -> Self {
+        let len = L::storage_len(&dims);
+        let data = (0..len).map(|_| generator()).collect::<Vec<_>>().into_boxed_slice();
+        Self::from_storage(dims, data)
+    }
+
+    /// Wraps `data` laid out in storage order of the given layout (padding included).
+    pub fn from_vec_in(dims: D, _layout: L, data: Vec<T>) -> Result<Self, LengthMismatch> {
+        let expected = L::storage_len(&dims);
+        if data.len() != expected {
+            return Err(LengthMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+        Ok(Self::from_storage(dims, data.into_boxed_slice()))
+    }
+
+    /// Takes the data out, in storage order.
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        self.data
+    }
+
+    /// Takes the data out, in storage order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data.into_vec()
+    }
+}
+
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// New array of `f` applied to every element (padding included), same dims and layout.
+    /// Result is heap-allocated.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Array3D<U, D, L>
+    where
+        S: IntoIterator<Item = T>,
+    {
+        let data = self.data.into_iter().map(f).collect();
+        Array3D::from_storage(self.dims, data)
+    }
+
+    /// New array of `f` applied to elements of `self` and `other` at the same position.
+    /// Panics if dimensions differ. Result is heap-allocated.
+    pub fn zip_map<U, V, S2: Storage<U>>(
+        &self,
+        other: &Array3D<U, D, L, S2>,
+        mut f: impl FnMut(&T, &U) -> V,
+    ) -> Array3D<V, D, L> {
+        assert_eq!(
+            self.dimensions(),
+            other.dimensions(),
+            "zip_map of differently sized arrays"
+        );
+        let data = self.data.iter().zip(other.data.iter()).map(|(a, b)| f(a, b)).collect();
+        Array3D::from_storage(self.dims, data)
+    }
+}
+
+// constructors for the default layout, so it does not have to be spelled out (like HashMap::new)
+impl<T: Clone, D: Dim3> Array3D<T, D> {
+    /// Creates a new array with all elements cloned from `value`.
+    pub fn new_filled(dims: D, value: T) -> Self {
+        Self::new_filled_in(dims, Linear, value)
+    }
+}
+
 impl<T: Clone + Default, D: Dim3> Array3D<T, D> {
     /// Creates array filled with `T::default()`.
     pub fn new_default(dims: D) -> Self {
-        Self::new_filled(dims, T::default())
+        Self::new_default_in(dims, Linear)
     }
 }
 
 impl<T, D: Dim3> Array3D<T, D> {
     /// Creates array via a generator function.
     pub fn from_fn<F: Fn() -> T>(dims: D, generator: F) -> Self {
-        let len = dims.total_len();
-        let data = (0..len).map(|_| generator()).collect::<Vec<_>>().into_boxed_slice();
-        Self::from_boxed(dims, data)
+        Self::from_fn_in(dims, Linear, generator)
+    }
+
+    /// Creates array via a generator called with the position of each element.
+    pub fn from_fn_pos(dims: D, generator: impl FnMut(Vec3<usize>) -> T) -> Self {
+        // linear storage is x-fastest, same as positions
+        let data = Positions::new(Vec3::zero(), dims.xyz()).map(generator).collect();
+        Self::from_storage(dims, data)
+    }
+
+    /// Wraps `data` laid out x-fastest (`x + y * X + z * X * Y`).
+    pub fn from_vec(dims: D, data: Vec<T>) -> Result<Self, LengthMismatch> {
+        Self::from_vec_in(dims, Linear, data)
     }
 }
 
@@ -237,7 +440,7 @@ impl ToUsize3 for Vec4<i32> {
     }
 }
 
-impl<T, D: Dim3, I: ToUsize3> Index<I> for Array3D<T, D> {
+impl<T, D: Dim3, L: Layout, S: Storage<T>, I: ToUsize3> Index<I> for Array3D<T, D, L, S> {
     type Output = T;
     fn index(&self, index: I) -> &Self::Output {
         let (x, y, z) = index.to_usize3();
@@ -245,14 +448,71 @@ impl<T, D: Dim3, I: ToUsize3> Index<I> for Array3D<T, D> {
     }
 }
 
-impl<T, D: Dim3, I: ToUsize3> IndexMut<I> for Array3D<T, D> {
+impl<T, D: Dim3, L: Layout, S: Storage<T>, I: ToUsize3> IndexMut<I> for Array3D<T, D, L, S> {
     fn index_mut(&mut self, index: I) -> &mut Self::Output {
         let (x, y, z) = index.to_usize3();
         self.get_mut(x, y, z)
     }
 }
 
-impl<T: Debug, D: Dim3> Debug for Array3D<T, D> {
+/// Compares dims and elements, padding is ignored.
+impl<T: PartialEq, D: Dim3, L: Layout, S: Storage<T>> PartialEq for Array3D<T, D, L, S> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.dimensions() != other.dimensions() {
+            return false;
+        }
+        if !self.has_padding() {
+            return *self.data == *other.data;
+        }
+        self.indexed_iter().zip(other.indexed_iter()).all(|((_, a), (_, b))| a == b)
+    }
+}
+
+impl<T: Eq, D: Dim3, L: Layout, S: Storage<T>> Eq for Array3D<T, D, L, S> {}
+
+/// Hashes dims and elements, padding is ignored.
+impl<T: Hash, D: Dim3, L: Layout, S: Storage<T>> Hash for Array3D<T, D, L, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dimensions().hash(state);
+        if !self.has_padding() {
+            (*self.data).hash(state);
+        } else {
+            self.indexed_iter().for_each(|(_, value)| value.hash(state));
+        }
+    }
+}
+
+/// Consumes the array, yielding elements in storage order (including padding).
+impl<T, D: Dim3, L: Layout, S: Storage<T> + IntoIterator<Item = T>> IntoIterator
+    for Array3D<T, D, L, S>
+{
+    type IntoIter = S::IntoIter;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> IntoIterator for &'a Array3D<T, D, L, S> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> IntoIterator for &'a mut Array3D<T, D, L, S> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: Debug, D: Dim3, L: Layout, S: Storage<T>> Debug for Array3D<T, D, L, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let dims = self.dimensions();
         writeln!(f, "Array3D [{:?} x {:?} x {:?}]:", dims.x, dims.y, dims.z)?;
@@ -269,6 +529,46 @@ impl<T: Debug, D: Dim3> Debug for Array3D<T, D> {
     }
 }
 
+/// 8x1x1 dims claiming no elements, like a product that wrapped in release.
+#[cfg(test)]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct WrappedDims;
+
+#[cfg(test)]
+impl Dim3 for WrappedDims {
+    fn x(&self) -> usize {
+        8
+    }
+    fn y(&self) -> usize {
+        1
+    }
+    fn z(&self) -> usize {
+        1
+    }
+    fn total_len(&self) -> usize {
+        0
+    }
+}
+
+/// Test value of the element at `pos`: its coordinates as decimal digits, `x + y * 10 + z * 100`.
+#[cfg(test)]
+pub(crate) fn number<T>(pos: Vec3<T>) -> T
+where
+    T: std::ops::Add<Output = T> + std::ops::Mul<Output = T> + From<u8>,
+{
+    pos.x + pos.y * T::from(10) + pos.z * T::from(100)
+}
+
+/// Test array in `layout`, every element set to `number` of its position.
+#[cfg(test)]
+pub(crate) fn numbered<L: Layout>(dims: RuntimeDims, layout: L) -> Array3D<usize, RuntimeDims, L> {
+    let mut array = Array3D::new_default_in(dims, layout);
+    for (pos, value) in array.indexed_iter_mut() {
+        *value = number(pos);
+    }
+    array
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -283,4 +583,120 @@ mod tests {
         s[(1, 1, 1)] = 8;
         assert_eq!(s[(1, 1, 1)], 8);
     }
+
+    #[test]
+    fn test_layouts_same_access() {
+        use crate::layout::{Brick8, Morton};
+
+        let dims = RuntimeDims { x: 5, y: 9, z: 3 };
+        let mut linear = Array3D::<u32, _>::new_default(dims);
+        let mut morton = Array3D::<u32, _, _>::new_default_in(dims, Morton);
+        let mut bricked = Array3D::<u32, _, _>::new_default_in(dims, Brick8::default());
+        for z in 0..dims.z {
+            for y in 0..dims.y {
+                for x in 0..dims.x {
+                    let value = number(Vec3::new(x, y, z)) as u32;
+                    linear.set(x, y, z, value);
+                    morton[(x, y, z)] = value;
+                    *bricked.get_mut(x, y, z) = value;
+                }
+            }
+        }
+        for z in 0..dims.z {
+            for y in 0..dims.y {
+                for x in 0..dims.x {
+                    assert_eq!(linear[(x, y, z)], morton[(x, y, z)]);
+                    assert_eq!(linear[(x, y, z)], *bricked.get(x, y, z));
+                }
+            }
+        }
+        assert_eq!(bricked.data.len(), 8 * 16 * 8);
+    }
+
+    #[test]
+    fn test_fallible_access() {
+        let mut array = Array3D::new_filled(RuntimeDims { x: 3, y: 2, z: 2 }, 0i32);
+        array[(2, 1, 1)] = 5;
+        assert_eq!(array.try_get(2, 1, 1), Some(&5));
+        // x past the row must not land in the next one
+        assert_eq!(array.try_get(3, 0, 0), None);
+        assert_eq!(array.try_get(0, 0, 2), None);
+        *array.try_get_mut(0, 1, 0).unwrap() = 1;
+        assert!(array.try_get_mut(0, 2, 0).is_none());
+
+        assert_eq!(array.get_signed(Vec3::new(2, 1, 1)), Some(&5));
+        assert_eq!(array.get_signed(Vec3::new(-1, 0, 0)), None);
+        assert_eq!(array.get_signed(Vec3::new(0, 0, i32::MIN)), None);
+        *array.get_signed_mut(Vec3::new(1, 1, 1)).unwrap() = 7;
+        assert_eq!(array[(1, 1, 1)], 7);
+    }
+
+    #[test]
+    fn test_construction_and_conversion() {
+        let dims = RuntimeDims { x: 3, y: 2, z: 2 };
+        let array = Array3D::from_fn_pos(dims, number);
+        assert_eq!(array[(2, 1, 1)], 112);
+        let data = array.into_vec();
+        assert_eq!(data[..4], [0, 1, 2, 10]);
+
+        assert_eq!(
+            Array3D::from_vec(dims, vec![0; 5]).unwrap_err(),
+            LengthMismatch {
+                expected: 12,
+                actual: 5
+            }
+        );
+        let array = Array3D::from_vec(dims, data).unwrap();
+        let halves = array.map(|v| v as f32 / 2.0);
+        assert_eq!(halves[(1, 1, 0)], 5.5);
+
+        let ones = Array3D::new_filled(dims, 1.0f32);
+        let sum = halves.zip_map(&ones, |a, b| a + b);
+        assert_eq!(sum[(1, 1, 0)], 6.5);
+        assert_eq!(sum.into_boxed_slice().len(), 12);
+    }
+
+    #[test]
+    fn test_std_traits() {
+        use crate::layout::Morton;
+        use std::collections::HashSet;
+
+        let dims = RuntimeDims { x: 3, y: 2, z: 2 };
+        let array = Array3D::from_fn_pos(dims, number);
+        let mut copy = array.clone();
+        assert_eq!(copy, array);
+        copy[(0, 0, 0)] = 1;
+        assert_ne!(copy, array);
+        for value in &mut copy {
+            *value += 1;
+        }
+        assert_eq!((&copy).into_iter().count(), 12);
+        assert_eq!(copy.into_iter().max(), Some(113));
+
+        // padding differs, elements don't
+        let mut a = Array3D::new_filled_in(dims, Morton, 0);
+        let mut b = Array3D::new_filled_in(dims, Morton, 7);
+        for (pos, value) in a.indexed_iter_mut().chain(b.indexed_iter_mut()) {
+            *value = pos.x + pos.y;
+        }
+        assert!(a.has_padding());
+        assert_eq!(a, b);
+        let set: HashSet<_> = [a, b].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_row_overflow_panics() {
+        let array = Array3D::new_filled(RuntimeDims { x: 3, y: 2, z: 2 }, 0u8);
+        // same flat index as (0, 1, 0)
+        let _ = array[(3, 0, 0)];
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_index_panics() {
+        let array = Array3D::new_filled(RuntimeDims { x: 3, y: 2, z: 2 }, 0u8);
+        let _ = array[(-1i32, 0, 0)];
+    }
 }
@@ -0,0 +1,254 @@
+use crate::array3d::*;
+use crate::layout::Layout;
+use qvek::vek::Vec3;
+use std::marker::PhantomData;
+
+/// Iterator over positions of box `min..max`, x-fastest.
+/// Steps coordinates with carries instead of dividing a flat counter.
+#[derive(Clone, Debug)]
+pub struct Positions {
+    next: Vec3<usize>,
+    min: Vec3<usize>,
+    max: Vec3<usize>,
+    remaining: usize,
+}
+
+impl Positions {
+    /// Positions of box `min..max` (`max` exclusive).
+    pub fn new(min: Vec3<usize>, max: Vec3<usize>) -> Self {
+        let size = Vec3::new(
+            max.x.saturating_sub(min.x),
+            max.y.saturating_sub(min.y),
+            max.z.saturating_sub(min.z),
+        );
+        Self {
+            next: min,
+            min,
+            max,
+            remaining: size.x * size.y * size.z,
+        }
+    }
+}
+
+impl Iterator for Positions {
+    type Item = Vec3<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let current = self.next;
+        self.next.x += 1;
+        if self.next.x == self.max.x {
+            self.next.x = self.min.x;
+            self.next.y += 1;
+            if self.next.y == self.max.y {
+                self.next.y = self.min.y;
+                self.next.z += 1;
+            }
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Positions {}
+
+/// Immutable iterator over (`position`, `&T`), x-fastest.
+pub struct IndexedIter<'a, T, D: Dim3, L: Layout> {
+    data: &'a [T],
+    dims: D,
+    positions: Positions,
+    _layout: PhantomData<L>,
+}
+
+impl<'a, T, D: Dim3, L: Layout> Clone for IndexedIter<'a, T, D, L> {
+    fn clone(&self) -> Self {
+        Self {
+            positions: self.positions.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout> Iterator for IndexedIter<'a, T, D, L> {
+    type Item = (Vec3<usize>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.positions.next()?;
+        let index = L::index(&self.dims, pos.x, pos.y, pos.z);
+        // checked, dims are public and can claim less storage than they index
+        Some((pos, &self.data[index]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout> ExactSizeIterator for IndexedIter<'a, T, D, L> {}
+
+/// Mutable iterator over (`position`, `&mut T`), x-fastest.
+pub struct IndexedIterMut<'a, T, D: Dim3, L: Layout> {
+    data: *mut T,
+    // storage length, every index is checked against it before touching `data`
+    len: usize,
+    dims: D,
+    positions: Positions,
+    _phantom: PhantomData<(&'a mut T, L)>,
+}
+
+impl<'a, T, D: Dim3, L: Layout> Iterator for IndexedIterMut<'a, T, D, L> {
+    type Item = (Vec3<usize>, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.positions.next()?;
+        let index = L::index(&self.dims, pos.x, pos.y, pos.z);
+        assert!(
+            index < self.len,
+            "index {index} out of storage of {} elements",
+            self.len
+        );
+        // every position is visited once and Layout guarantees distinct indices
+        Some((pos, unsafe { &mut *self.data.add(index) }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout> ExactSizeIterator for IndexedIterMut<'a, T, D, L> {}
+
+// extend behaviour of array to support coordinate-aware iteration
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// Positions of every element, x-fastest.
+    pub fn positions(&self) -> Positions {
+        Positions::new(Vec3::zero(), self.dimensions())
+    }
+
+    /// Positions of box `min..max` (`max` exclusive), x-fastest.
+    pub fn positions_region(&self, min: Vec3<usize>, max: Vec3<usize>) -> Positions {
+        check_region(self.dimensions(), min, max);
+        Positions::new(min, max)
+    }
+
+    /// Iterates (`position`, `&T`) of every element, x-fastest. Padding is skipped.
+    pub fn indexed_iter(&self) -> IndexedIter<'_, T, D, L> {
+        self.check_storage();
+        IndexedIter {
+            data: &self.data,
+            dims: self.dims,
+            positions: self.positions(),
+            _layout: PhantomData,
+        }
+    }
+
+    /// Iterates (`position`, `&mut T`) of every element, x-fastest. Padding is skipped.
+    pub fn indexed_iter_mut(&mut self) -> IndexedIterMut<'_, T, D, L> {
+        self.check_storage();
+        IndexedIterMut {
+            data: self.data.as_mut_ptr(),
+            len: self.data.len(),
+            dims: self.dims,
+            positions: self.positions(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Iterates (`position`, `&T`) of box `min..max` (`max` exclusive), in array coordinates.
+    pub fn indexed_iter_region(
+        &self,
+        min: Vec3<usize>,
+        max: Vec3<usize>,
+    ) -> IndexedIter<'_, T, D, L> {
+        self.check_storage();
+        IndexedIter {
+            data: &self.data,
+            dims: self.dims,
+            positions: self.positions_region(min, max),
+            _layout: PhantomData,
+        }
+    }
+
+    /// Iterates (`position`, `&mut T`) of box `min..max` (`max` exclusive), in array coordinates.
+    pub fn indexed_iter_region_mut(
+        &mut self,
+        min: Vec3<usize>,
+        max: Vec3<usize>,
+    ) -> IndexedIterMut<'_, T, D, L> {
+        self.check_storage();
+        IndexedIterMut {
+            data: self.data.as_mut_ptr(),
+            len: self.data.len(),
+            dims: self.dims,
+            positions: self.positions_region(min, max),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Morton;
+
+    #[test]
+    fn test_positions_match_linear_order() {
+        let array = Array3D::new_filled(RuntimeDims::new(3, 2, 4), 0u8);
+        assert_eq!(array.positions().len(), 24);
+        for (index, pos) in array.positions().enumerate() {
+            assert_eq!(array.index_internal(pos.x, pos.y, pos.z), index);
+        }
+    }
+
+    #[test]
+    fn test_indexed_iter() {
+        let dims = RuntimeDims::new(4, 3, 5);
+        let mut array = numbered(dims, Morton);
+        assert_eq!(array[(3, 2, 4)], 423);
+        assert!(array.indexed_iter().all(|(pos, &v)| v == number(pos)));
+
+        let region: Vec<_> =
+            array.indexed_iter_region(Vec3::new(1, 1, 1), Vec3::new(3, 2, 2)).collect();
+        assert_eq!(
+            region,
+            vec![(Vec3::new(1, 1, 1), &111), (Vec3::new(2, 1, 1), &112)]
+        );
+
+        for (_, value) in array.indexed_iter_region_mut(Vec3::zero(), Vec3::new(1, 1, 1)) {
+            *value = 7;
+        }
+        assert_eq!(array[(0, 0, 0)], 7);
+        assert_eq!(
+            array.positions_region(Vec3::new(1, 1, 1), Vec3::new(1, 3, 3)).count(),
+            0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "too short")]
+    fn test_indexed_iter_checks_storage() {
+        let mut array = Array3D::new_filled(RuntimeDims::new(2, 2, 2), 0i32);
+        array.data = vec![0; 1].into_boxed_slice();
+        array.indexed_iter().map(|(_, v)| v).sum::<i32>();
+    }
+
+    #[test]
+    #[should_panic(expected = "out of")]
+    fn test_indexed_iter_checks_wrapped_dims() {
+        let array = Array3D::new_filled(WrappedDims, 0u8);
+        array.indexed_iter_region(Vec3::new(2, 0, 0), Vec3::new(4, 1, 1)).count();
+    }
+
+    #[test]
+    #[should_panic(expected = "out of storage")]
+    fn test_indexed_iter_mut_checks_wrapped_dims() {
+        let mut array = Array3D::new_filled(WrappedDims, 0u8);
+        array.indexed_iter_region_mut(Vec3::new(2, 0, 0), Vec3::new(4, 1, 1)).count();
+    }
+}
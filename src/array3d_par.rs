@@ -0,0 +1,107 @@
+use crate::array3d::*;
+use crate::array3d_iter::Positions;
+use crate::layout::Layout;
+use crate::multiprocessor::Multiprocessor;
+use qvek::vek::Vec3;
+use std::sync::Mutex;
+
+/// Splits `0..len` into `parts` ranges of (almost) equal size, returned as (start, end).
+fn split_even(len: usize, parts: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..parts).map(move |i| (len * i / parts, len * (i + 1) / parts))
+}
+
+/// One slab per thread, fewer if there are not enough z layers.
+fn slab_count(pool: &Multiprocessor, depth: usize) -> usize {
+    pool.used_thread_count().max(1).min(depth)
+}
+
+// extend behaviour of array to support processing on the thread pool
+impl<T: Send, D: Dim3 + Send, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// Calls `f` with the position and element for every element, spread over `pool` in disjoint z-slabs.
+    pub fn par_for_each_mut<F>(&mut self, pool: &Multiprocessor, f: F)
+    where
+        F: Fn(Vec3<usize>, &mut T) + Sync,
+    {
+        let dims = self.dimensions();
+        if dims.x * dims.y * dims.z == 0 {
+            return;
+        }
+        let count = slab_count(pool, dims.z);
+        let mut rest = self.region_mut(Vec3::zero(), dims);
+        let mut slabs = Vec::with_capacity(count);
+        let mut taken = 0;
+        for (_, end) in split_even(dims.z, count) {
+            let (slab, remaining) = rest.split_at_z(end - taken);
+            taken = end;
+            slabs.push(Mutex::new(Some(slab)));
+            rest = remaining;
+        }
+
+        pool.dispatch_scoped(count, |thread_id| {
+            let Some(mut slab) = slabs[thread_id].lock().unwrap().take() else {
+                return;
+            };
+            let min = slab.min();
+            for local in Positions::new(Vec3::zero(), slab.dimensions()) {
+                f(min + local, slab.get_mut(local.x, local.y, local.z));
+            }
+        });
+    }
+}
+
+impl<T: Sync, D: Dim3 + Sync, L: Layout + Sync, S: Storage<T> + Sync> Array3D<T, D, L, S> {
+    /// Maps every element with `f` on `pool`, split in z-slabs. Result is in the default (linear) layout.
+    pub fn par_map<U, F>(&self, pool: &Multiprocessor, f: F) -> Array3D<U, D>
+    where
+        U: Send,
+        F: Fn(Vec3<usize>, &T) -> U + Sync,
+    {
+        let dims = self.dimensions();
+        let count = slab_count(pool, dims.z);
+        let results: Vec<Mutex<Vec<U>>> = (0..count).map(|_| Mutex::new(Vec::new())).collect();
+        let slabs: Vec<_> = split_even(dims.z, count).collect();
+
+        pool.dispatch_scoped(count, |thread_id| {
+            let (start, end) = slabs[thread_id];
+            let min = Vec3::new(0, 0, start);
+            let max = Vec3::new(dims.x, dims.y, end);
+            let mapped =
+                self.indexed_iter_region(min, max).map(|(pos, value)| f(pos, value)).collect();
+            *results[thread_id].lock().unwrap() = mapped;
+        });
+
+        // slabs are whole xy layers in z order, so concatenating gives linear order
+        let data: Vec<U> =
+            results.into_iter().flat_map(|slab| slab.into_inner().unwrap()).collect();
+        Array3D::from_storage(self.dims, data.into_boxed_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Brick8;
+
+    #[test]
+    fn test_par_for_each_mut_and_map() {
+        // real workers, even on a single core machine
+        let pool = Multiprocessor::with_threads(3);
+        let dims = RuntimeDims::new(5, 3, 17);
+        let mut array = Array3D::new_default_in(dims, Brick8::default());
+        array.par_for_each_mut(&pool, |pos, value| {
+            *value = number(pos);
+        });
+        assert!(array.indexed_iter().all(|(pos, &v)| v == number(pos)));
+
+        let doubled = array.par_map(&pool, |_, &v| v * 2);
+        assert_eq!(doubled.dimensions(), dims);
+        assert_eq!(doubled[(4, 2, 16)], 2 * 1624);
+        assert!(doubled.indexed_iter().all(|(pos, &v)| v == 2 * array[(pos.x, pos.y, pos.z)]));
+
+        // borrowing from the caller is fine
+        let offset = 7;
+        let mut small = Array3D::new_filled(ConstDims::<2, 2, 1>, 0);
+        small.par_for_each_mut(&pool, |_, value| *value += offset);
+        assert!(small.iter().all(|&v| v == 7));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::array3d::*;
+use crate::array3d_iter::Positions;
+use crate::layout::Layout;
+use qvek::vek::Vec3;
+
+/// Part of `0..new` that maps into `0..old` when content moves by `offset`, as a (min, max) box.
+fn overlap(old: Vec3<usize>, new: Vec3<usize>, offset: Vec3<i64>) -> (Vec3<usize>, Vec3<usize>) {
+    let axis = |old: usize, new: usize, offset: i64| {
+        let min = offset.clamp(0, new as i64);
+        let max = (old as i64 + offset).clamp(min, new as i64);
+        (min as usize, max as usize)
+    };
+    let (min_x, max_x) = axis(old.x, new.x, offset.x);
+    let (min_y, max_y) = axis(old.y, new.y, offset.y);
+    let (min_z, max_z) = axis(old.z, new.z, offset.z);
+    (
+        Vec3::new(min_x, min_y, min_z),
+        Vec3::new(max_x, max_y, max_z),
+    )
+}
+
+// extend behaviour of array to support changing its extent
+impl<T: Clone, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// Storage for `dims` where (x, y, z) holds the old (x, y, z) - `offset`, and `fill` everywhere else.
+    fn remapped<D2: Dim3>(&self, dims: D2, offset: Vec3<i64>, fill: T) -> Box<[T]> {
+        let mut data = vec![fill; L::storage_len(&dims)];
+        let (min, max) = overlap(self.dimensions(), dims.xyz(), offset);
+        for pos in Positions::new(min, max) {
+            let src = pos.map(|c| c as i64) - offset;
+            data[L::index(&dims, pos.x, pos.y, pos.z)] =
+                self.get(src.x as usize, src.y as usize, src.z as usize).clone();
+        }
+        data.into_boxed_slice()
+    }
+
+    /// Moves content by `offset` (dims stay), cells it left behind are set to `fill`.
+    /// Content moved past the edge is lost.
+    pub fn shift(&mut self, offset: Vec3<i32>, fill: T) {
+        let data = self.remapped(self.dims, offset.map(i64::from), fill);
+        // same dims, so the new storage lines up with the old one
+        for (slot, value) in self.data.iter_mut().zip(data) {
+            *slot = value;
+        }
+    }
+}
+
+impl<T: Clone, L: Layout> Array3D<T, RuntimeDims, L> {
+    /// Changes dims to `new_dims`, keeping content at the origin. New cells are set to `fill`.
+    pub fn resize(&mut self, new_dims: RuntimeDims, fill: T) {
+        *self = Self::from_storage(new_dims, self.remapped(new_dims, Vec3::zero(), fill));
+    }
+
+    /// Shrinks to box `min..max` (`max` exclusive), which becomes the new origin.
+    pub fn crop(&mut self, min: Vec3<usize>, max: Vec3<usize>) {
+        // validates the box
+        let size = self.region(min, max).dimensions();
+        let offset = -min.map(|c| c as i64);
+        // every cell is copied from the old array, fill only ends up in layout padding
+        let data = match self.data.first() {
+            Some(any) => self.remapped(size, offset, any.clone()),
+            None => Box::new([]),
+        };
+        *self = Self::from_storage(size, data);
+    }
+
+    /// Grows by `before` cells at the low end and `after` cells at the high end of each axis, set to `fill`.
+    pub fn pad(&mut self, before: Vec3<usize>, after: Vec3<usize>, fill: T) {
+        let dims = self.dimensions() + before + after;
+        let offset = before.map(|c| c as i64);
+        *self = Self::from_storage(dims, self.remapped(dims, offset, fill));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Morton;
+
+    #[test]
+    fn test_resize_crop_pad() {
+        let mut array = numbered(RuntimeDims::new(3, 3, 3), Morton);
+        array.resize(RuntimeDims::new(4, 2, 3), 9);
+        assert_eq!(array.dimensions(), Vec3::new(4, 2, 3));
+        assert_eq!(array[(2, 1, 2)], 212);
+        assert_eq!(array[(3, 0, 0)], 9);
+
+        array.crop(Vec3::new(1, 1, 1), Vec3::new(3, 2, 3));
+        assert_eq!(array.dimensions(), Vec3::new(2, 1, 2));
+        assert_eq!(array[(0, 0, 0)], 111);
+        assert_eq!(array[(1, 0, 1)], 212);
+
+        array.pad(Vec3::new(1, 0, 0), Vec3::new(0, 2, 1), 7);
+        assert_eq!(array.dimensions(), Vec3::new(3, 3, 3));
+        assert_eq!(array[(1, 0, 0)], 111);
+        assert_eq!(array[(0, 0, 0)], 7);
+        assert_eq!(array[(2, 2, 2)], 7);
+
+        array.crop(Vec3::zero(), Vec3::zero());
+        assert_eq!(array.data.len(), 0);
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut array = numbered(RuntimeDims::new(3, 2, 2), Morton);
+        array.shift(Vec3::new(1, 0, -1), 0);
+        assert_eq!(array[(1, 0, 0)], 100);
+        assert_eq!(array[(2, 1, 0)], 111);
+        assert_eq!(array[(0, 1, 0)], 0);
+        assert_eq!(array[(1, 0, 1)], 0);
+
+        let mut fixed = Array3D::new_filled(ConstDims::<2, 2, 2>, 1u8);
+        fixed.shift(Vec3::new(5, 0, 0), 0);
+        assert!(fixed.iter().all(|&v| v == 0));
+    }
+}
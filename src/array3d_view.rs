@@ -1,18 +1,29 @@
 use qvek::vek::Vec3;
 
+use crate::array2d::DArray2D;
 use crate::array3d::*;
+use crate::array3d_iter::Positions;
+use crate::layout::{Layout, Linear};
 use std::{
     marker::PhantomData,
     ops::{Index, IndexMut},
 };
 
 /// Read-only view converting element type via `Into<U>`.
-pub struct Array3DView<'a, T, U, D: Dim3> {
-    array: &'a Array3D<T, D>,
+pub struct Array3DView<'a, T, U, D: Dim3, L: Layout = Linear, S = Box<[T]>> {
+    array: &'a Array3D<T, D, L, S>,
     _phantom: PhantomData<U>,
 }
 
-impl<'a, T, U, D: Dim3> Array3DView<'a, T, U, D>
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>> Clone for Array3DView<'a, T, U, D, L, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>> Copy for Array3DView<'a, T, U, D, L, S> {}
+
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>> Array3DView<'a, T, U, D, L, S>
 where
     T: Into<U> + Clone,
 {
@@ -22,13 +33,15 @@ where
     }
 }
 
-impl<'a, T, U, D: Dim3> Array3DView<'a, T, U, D> {
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>> Array3DView<'a, T, U, D, L, S> {
     pub fn dimensions(&self) -> Vec3<usize> {
         self.array.dimensions()
     }
 }
 
-impl<'a, T, U, D: Dim3, I: ToUsize3> Index<I> for Array3DView<'a, T, U, D> {
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>, I: ToUsize3> Index<I>
+    for Array3DView<'a, T, U, D, L, S>
+{
     type Output = T;
     fn index(&self, index: I) -> &Self::Output {
         let (x, y, z) = index.to_usize3();
@@ -37,12 +50,12 @@ impl<'a, T, U, D: Dim3, I: ToUsize3> Index<I> for Array3DView<'a, T, U, D> {
 }
 
 /// Mutable view converting element type via `From<U>`.
-pub struct Array3DViewMut<'a, T, U, D: Dim3> {
-    array: &'a mut Array3D<T, D>,
+pub struct Array3DViewMut<'a, T, U, D: Dim3, L: Layout = Linear, S = Box<[T]>> {
+    array: &'a mut Array3D<T, D, L, S>,
     _phantom: PhantomData<U>,
 }
 
-impl<'a, T, U, D: Dim3> Array3DViewMut<'a, T, U, D> {
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>> Array3DViewMut<'a, T, U, D, L, S> {
     pub fn set(&mut self, index: impl ToUsize3, value: U)
     where
         T: From<U>,
@@ -64,13 +77,15 @@ impl<'a, T, U, D: Dim3> Array3DViewMut<'a, T, U, D> {
     }
 }
 
-impl<'a, T: Clone, U, D: Dim3> Array3DViewMut<'a, T, U, D> {
+impl<'a, T: Clone, U, D: Dim3, L: Layout, S: Storage<T>> Array3DViewMut<'a, T, U, D, L, S> {
     pub fn fill(&mut self, value: T) {
         self.array.data.fill(value);
     }
 }
 
-impl<'a, T, U, D: Dim3, I: ToUsize3> Index<I> for Array3DViewMut<'a, T, U, D> {
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>, I: ToUsize3> Index<I>
+    for Array3DViewMut<'a, T, U, D, L, S>
+{
     type Output = T;
     fn index(&self, index: I) -> &Self::Output {
         let (x, y, z) = index.to_usize3();
@@ -78,7 +93,9 @@ impl<'a, T, U, D: Dim3, I: ToUsize3> Index<I> for Array3DViewMut<'a, T, U, D> {
     }
 }
 
-impl<'a, T, U, D: Dim3, I: ToUsize3> IndexMut<I> for Array3DViewMut<'a, T, U, D> {
+impl<'a, T, U, D: Dim3, L: Layout, S: Storage<T>, I: ToUsize3> IndexMut<I>
+    for Array3DViewMut<'a, T, U, D, L, S>
+{
     fn index_mut(&mut self, index: I) -> &mut Self::Output {
         let (x, y, z) = index.to_usize3();
         self.array.get_mut(x, y, z)
@@ -86,9 +103,9 @@ impl<'a, T, U, D: Dim3, I: ToUsize3> IndexMut<I> for Array3DViewMut<'a, T, U, D>
 }
 
 // extend behaviour of array to support views
-impl<T, D: Dim3> Array3D<T, D> {
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
     /// Creates a read-only converting view.
-    pub fn as_view<U>(&self) -> Array3DView<'_, T, U, D> {
+    pub fn as_view<U>(&self) -> Array3DView<'_, T, U, D, L, S> {
         Array3DView {
             array: self,
             _phantom: PhantomData,
@@ -96,10 +113,516 @@ impl<T, D: Dim3> Array3D<T, D> {
     }
 
     /// Creates a mutable converting view.
-    pub fn as_view_mut<U>(&mut self) -> Array3DViewMut<'_, T, U, D> {
+    pub fn as_view_mut<U>(&mut self) -> Array3DViewMut<'_, T, U, D, L, S> {
         Array3DViewMut {
             array: self,
             _phantom: PhantomData,
         }
     }
 }
+
+/// Read-only box-shaped region of an array, addressed in local coordinates (relative to `min`).
+pub struct SubView<'a, T, D: Dim3, L: Layout = Linear, S = Box<[T]>> {
+    array: &'a Array3D<T, D, L, S>,
+    min: Vec3<usize>,
+    size: Vec3<usize>,
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> Clone for SubView<'a, T, D, L, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> Copy for SubView<'a, T, D, L, S> {}
+
+/// Mutable box-shaped region of an array, addressed in local coordinates (relative to `min`).
+/// Can be split into non-overlapping regions that are mutated independently (e.g. from different threads).
+pub struct SubViewMut<'a, T, D: Dim3, L: Layout = Linear> {
+    // raw pointer, so sibling regions from `split_at_*` can coexist
+    data: *mut T,
+    // storage length, every index is checked against it before touching `data`
+    len: usize,
+    dims: D,
+    min: Vec3<usize>,
+    size: Vec3<usize>,
+    _phantom: PhantomData<(&'a mut T, L)>,
+}
+
+// same rules as &mut T
+unsafe impl<'a, T: Send, D: Dim3 + Send, L: Layout> Send for SubViewMut<'a, T, D, L> {}
+unsafe impl<'a, T: Sync, D: Dim3 + Sync, L: Layout> Sync for SubViewMut<'a, T, D, L> {}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> SubView<'a, T, D, L, S> {
+    /// Size of the region.
+    pub fn dimensions(&self) -> Vec3<usize> {
+        self.size
+    }
+
+    /// Corner of the region in array coordinates.
+    pub fn min(&self) -> Vec3<usize> {
+        self.min
+    }
+
+    /// Shared reference at local (x, y, z). Panics outside of the region.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &'a T {
+        assert!(
+            x < self.size.x && y < self.size.y && z < self.size.z,
+            "({x}, {y}, {z}) out of region {:?}",
+            self.size
+        );
+        self.array.get(self.min.x + x, self.min.y + y, self.min.z + z)
+    }
+
+    /// Narrows the view to `min..max` (local coordinates, `max` exclusive).
+    pub fn region(&self, min: Vec3<usize>, max: Vec3<usize>) -> SubView<'a, T, D, L, S> {
+        check_region(self.size, min, max);
+        SubView {
+            array: self.array,
+            min: self.min + min,
+            size: max - min,
+        }
+    }
+
+    /// Iterates elements in local x-fastest order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + use<'a, T, D, L, S> {
+        let view = *self;
+        Positions::new(Vec3::zero(), self.size).map(move |p| view.get(p.x, p.y, p.z))
+    }
+
+    /// Copies the region into a new (linear) array.
+    pub fn to_array(&self) -> Array3D<T, RuntimeDims>
+    where
+        T: Clone,
+    {
+        Array3D::from_storage(self.size, self.iter().cloned().collect())
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>, I: ToUsize3> Index<I> for SubView<'a, T, D, L, S> {
+    type Output = T;
+    fn index(&self, index: I) -> &Self::Output {
+        let (x, y, z) = index.to_usize3();
+        self.get(x, y, z)
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout> SubViewMut<'a, T, D, L> {
+    /// Size of the region.
+    pub fn dimensions(&self) -> Vec3<usize> {
+        self.size
+    }
+
+    /// Corner of the region in array coordinates.
+    pub fn min(&self) -> Vec3<usize> {
+        self.min
+    }
+
+    /// Storage index of local (x, y, z). Bounds are always checked, the region must not be escaped.
+    fn local_index(&self, x: usize, y: usize, z: usize) -> usize {
+        assert!(
+            x < self.size.x && y < self.size.y && z < self.size.z,
+            "({x}, {y}, {z}) out of region {:?}",
+            self.size
+        );
+        let index = L::index(&self.dims, self.min.x + x, self.min.y + y, self.min.z + z);
+        // dims are public and their product can wrap, so don't trust the storage check alone
+        assert!(
+            index < self.len,
+            "index {index} out of storage of {} elements",
+            self.len
+        );
+        index
+    }
+
+    /// Shared reference at local (x, y, z). Panics outside of the region.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &T {
+        let index = self.local_index(x, y, z);
+        unsafe { &*self.data.add(index) }
+    }
+
+    /// Mutable reference at local (x, y, z). Panics outside of the region.
+    pub fn get_mut(&mut self, x: usize, y: usize, z: usize) -> &mut T {
+        let index = self.local_index(x, y, z);
+        unsafe { &mut *self.data.add(index) }
+    }
+
+    /// Sets value at local (x, y, z). Panics outside of the region.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: T) {
+        *self.get_mut(x, y, z) = value;
+    }
+
+    /// Narrows the view to `min..max` (local coordinates, `max` exclusive).
+    pub fn region_mut(&mut self, min: Vec3<usize>, max: Vec3<usize>) -> SubViewMut<'_, T, D, L> {
+        check_region(self.size, min, max);
+        SubViewMut {
+            data: self.data,
+            len: self.len,
+            dims: self.dims,
+            min: self.min + min,
+            size: max - min,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Consumes the view, splitting it into `min..max` pieces that don't overlap.
+    fn split(self, first_max: Vec3<usize>, second_min: Vec3<usize>) -> (Self, Self) {
+        let first = SubViewMut {
+            size: first_max,
+            ..self
+        };
+        let second = SubViewMut {
+            min: self.min + second_min,
+            size: self.size - second_min,
+            ..self
+        };
+        (first, second)
+    }
+
+    /// Splits into `x < at` and `x >= at` (local coordinates).
+    pub fn split_at_x(self, at: usize) -> (Self, Self) {
+        assert!(at <= self.size.x);
+        let size = self.size;
+        self.split(Vec3::new(at, size.y, size.z), Vec3::new(at, 0, 0))
+    }
+
+    /// Splits into `y < at` and `y >= at` (local coordinates).
+    pub fn split_at_y(self, at: usize) -> (Self, Self) {
+        assert!(at <= self.size.y);
+        let size = self.size;
+        self.split(Vec3::new(size.x, at, size.z), Vec3::new(0, at, 0))
+    }
+
+    /// Splits into `z < at` and `z >= at` (local coordinates).
+    pub fn split_at_z(self, at: usize) -> (Self, Self) {
+        assert!(at <= self.size.z);
+        let size = self.size;
+        self.split(Vec3::new(size.x, size.y, at), Vec3::new(0, 0, at))
+    }
+
+    /// Iterates elements in local x-fastest order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        Positions::new(Vec3::zero(), self.size).map(move |p| self.get(p.x, p.y, p.z))
+    }
+
+    /// Mutably iterates elements in local x-fastest order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        let (data, size) = (self.data, self.size);
+        let this = &*self;
+        // layouts are injective, so every position yields a distinct element
+        Positions::new(Vec3::zero(), size)
+            .map(move |p| unsafe { &mut *data.add(this.local_index(p.x, p.y, p.z)) })
+    }
+
+    /// Copies `src` into this region. Sizes must match.
+    pub fn copy_from<D2: Dim3, L2: Layout, S2: Storage<T>>(
+        &mut self,
+        src: &SubView<'_, T, D2, L2, S2>,
+    ) where
+        T: Clone,
+    {
+        assert_eq!(self.size, src.dimensions(), "region sizes differ");
+        for (dst, value) in self.iter_mut().zip(src.iter()) {
+            dst.clone_from(value);
+        }
+    }
+
+    /// Fills the region with `value`.
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        for dst in self.iter_mut() {
+            *dst = value.clone();
+        }
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, I: ToUsize3> Index<I> for SubViewMut<'a, T, D, L> {
+    type Output = T;
+    fn index(&self, index: I) -> &Self::Output {
+        let (x, y, z) = index.to_usize3();
+        self.get(x, y, z)
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, I: ToUsize3> IndexMut<I> for SubViewMut<'a, T, D, L> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        let (x, y, z) = index.to_usize3();
+        self.get_mut(x, y, z)
+    }
+}
+
+// extend behaviour of array to support sub-region views
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// Borrows box `min..max` (`max` exclusive) as a view in local coordinates.
+    pub fn region(&self, min: Vec3<usize>, max: Vec3<usize>) -> SubView<'_, T, D, L, S> {
+        self.check_storage();
+        check_region(self.dimensions(), min, max);
+        SubView {
+            array: self,
+            min,
+            size: max - min,
+        }
+    }
+
+    /// Mutably borrows box `min..max` (`max` exclusive) as a view in local coordinates.
+    pub fn region_mut(&mut self, min: Vec3<usize>, max: Vec3<usize>) -> SubViewMut<'_, T, D, L> {
+        // view indexes through a raw pointer, without bounds checks
+        self.check_storage();
+        check_region(self.dimensions(), min, max);
+        SubViewMut {
+            data: self.data.as_mut_ptr(),
+            len: self.data.len(),
+            dims: self.dims,
+            min,
+            size: max - min,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Copies `src` into this array with its corner at `dst_min`.
+    pub fn copy_region_from<D2: Dim3, L2: Layout, S2: Storage<T>>(
+        &mut self,
+        dst_min: Vec3<usize>,
+        src: &SubView<'_, T, D2, L2, S2>,
+    ) where
+        T: Clone,
+    {
+        self.region_mut(dst_min, dst_min + src.dimensions()).copy_from(src);
+    }
+}
+
+/// Axis-aligned plane of a 3D array. 2D coordinates (u, v) map to the two named axes in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Plane {
+    /// (u, v) = (x, y), fixed z.
+    XY,
+    /// (u, v) = (x, z), fixed y.
+    XZ,
+    /// (u, v) = (y, z), fixed x.
+    YZ,
+}
+
+impl Plane {
+    /// 3D position of (u, v) on this plane at `depth` along the remaining axis.
+    pub fn to_xyz(self, u: usize, v: usize, depth: usize) -> (usize, usize, usize) {
+        match self {
+            Plane::XY => (u, v, depth),
+            Plane::XZ => (u, depth, v),
+            Plane::YZ => (depth, u, v),
+        }
+    }
+
+    /// (u size, v size, depth size) of this plane for array `dims`.
+    fn split(self, dims: Vec3<usize>) -> (usize, usize, usize) {
+        match self {
+            Plane::XY => (dims.x, dims.y, dims.z),
+            Plane::XZ => (dims.x, dims.z, dims.y),
+            Plane::YZ => (dims.y, dims.z, dims.x),
+        }
+    }
+}
+
+/// Read-only 2D slice of a 3D array along a `Plane`.
+pub struct Slice2D<'a, T, D: Dim3, L: Layout = Linear, S = Box<[T]>> {
+    array: &'a Array3D<T, D, L, S>,
+    plane: Plane,
+    depth: usize,
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> Clone for Slice2D<'a, T, D, L, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> Copy for Slice2D<'a, T, D, L, S> {}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> Slice2D<'a, T, D, L, S> {
+    /// Returns (u, v) dimensions of the slice.
+    pub fn dimensions(&self) -> (usize, usize) {
+        let (u, v, _) = self.plane.split(self.array.dimensions());
+        (u, v)
+    }
+
+    pub fn plane(&self) -> Plane {
+        self.plane
+    }
+
+    /// Shared reference at (u, v).
+    pub fn get(&self, u: usize, v: usize) -> &'a T {
+        let (x, y, z) = self.plane.to_xyz(u, v, self.depth);
+        self.array.get(x, y, z)
+    }
+
+    /// Iterates elements u-fastest, same order as `DArray2D` storage.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + use<'a, T, D, L, S> {
+        let slice = *self;
+        let (u_size, v_size) = self.dimensions();
+        (0..v_size).flat_map(move |v| (0..u_size).map(move |u| slice.get(u, v)))
+    }
+
+    /// Copies the slice into a new 2D array.
+    pub fn to_darray2d(&self) -> DArray2D<T>
+    where
+        T: Clone,
+    {
+        let (x_size, y_size) = self.dimensions();
+        DArray2D {
+            data: self.iter().cloned().collect(),
+            x_size,
+            y_size,
+        }
+    }
+}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> Index<(usize, usize)> for Slice2D<'a, T, D, L, S> {
+    type Output = T;
+    fn index(&self, (u, v): (usize, usize)) -> &Self::Output {
+        self.get(u, v)
+    }
+}
+
+// extend behaviour of array to support 2D slices
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// Borrows plane at `depth` along the axis `plane` does not contain.
+    pub fn slice(&self, plane: Plane, depth: usize) -> Slice2D<'_, T, D, L, S> {
+        let (_, _, depth_size) = plane.split(self.dimensions());
+        assert!(
+            depth < depth_size,
+            "{plane:?} slice {depth} out of bounds {depth_size}"
+        );
+        Slice2D {
+            array: self,
+            plane,
+            depth,
+        }
+    }
+
+    /// Borrows XY plane at `z`, indexed by (x, y).
+    pub fn slice_xy(&self, z: usize) -> Slice2D<'_, T, D, L, S> {
+        self.slice(Plane::XY, z)
+    }
+
+    /// Borrows XZ plane at `y`, indexed by (x, z).
+    pub fn slice_xz(&self, y: usize) -> Slice2D<'_, T, D, L, S> {
+        self.slice(Plane::XZ, y)
+    }
+
+    /// Borrows YZ plane at `x`, indexed by (y, z).
+    pub fn slice_yz(&self, x: usize) -> Slice2D<'_, T, D, L, S> {
+        self.slice(Plane::YZ, x)
+    }
+
+    /// Pastes `src` into plane at `depth`. Sizes must match.
+    pub fn write_slice(&mut self, plane: Plane, depth: usize, src: &DArray2D<T>)
+    where
+        T: Clone,
+    {
+        let (u_size, v_size, depth_size) = plane.split(self.dimensions());
+        assert!(
+            depth < depth_size,
+            "{plane:?} slice {depth} out of bounds {depth_size}"
+        );
+        assert_eq!((u_size, v_size), src.dimensions(), "slice sizes differ");
+        for v in 0..v_size {
+            for u in 0..u_size {
+                let (x, y, z) = plane.to_xyz(u, v, depth);
+                self.get_mut(x, y, z).clone_from(src.get_ref(u, v));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_local_coordinates() {
+        let dims = RuntimeDims::new(4, 4, 4);
+        let mut array = Array3D::new_filled(dims, 0u32);
+        {
+            let mut region = array.region_mut(Vec3::new(1, 2, 3), Vec3::new(3, 4, 4));
+            assert_eq!(region.dimensions(), Vec3::new(2, 2, 1));
+            region[(1, 1, 0)] = 7;
+            region.set(0, 0, 0, 5);
+        }
+        assert_eq!(array[(2, 3, 3)], 7);
+        assert_eq!(array[(1, 2, 3)], 5);
+
+        let view = array.region(Vec3::new(1, 2, 3), Vec3::new(3, 4, 4));
+        assert_eq!(view.iter().copied().collect::<Vec<_>>(), vec![5, 0, 0, 7]);
+        assert_eq!(view.to_array().dimensions(), Vec3::new(2, 2, 1));
+    }
+
+    #[test]
+    fn test_split_and_copy() {
+        let dims = RuntimeDims::new(4, 2, 6);
+        let mut array = Array3D::new_filled(dims, 0u8);
+        let whole = array.region_mut(Vec3::zero(), dims);
+        let (mut low, high) = whole.split_at_z(2);
+        let (mut mid, mut top) = high.split_at_x(1);
+        low.fill(1);
+        mid.fill(2);
+        top.fill(3);
+        assert_eq!(array.iter().filter(|&&v| v == 1).count(), 4 * 2 * 2);
+        assert_eq!(array.iter().filter(|&&v| v == 2).count(), 2 * 4);
+        assert_eq!(array.iter().filter(|&&v| v == 3).count(), 3 * 2 * 4);
+
+        let source = Array3D::new_filled(ConstDims::<2, 2, 2>, 9u8);
+        array.copy_region_from(
+            Vec3::new(2, 0, 4),
+            &source.region(Vec3::zero(), Vec3::new(2, 2, 2)),
+        );
+        assert_eq!(array[(3, 1, 5)], 9);
+        assert_eq!(array[(0, 1, 5)], 2);
+    }
+
+    #[test]
+    fn test_slices() {
+        let dims = RuntimeDims::new(3, 4, 5);
+        let mut array = Array3D::new_filled(dims, 0usize);
+        for (i, value) in array.iter_mut().enumerate() {
+            *value = i;
+        }
+
+        let xz = array.slice_xz(2);
+        assert_eq!(xz.dimensions(), (3, 5));
+        assert_eq!(xz[(1, 4)], array[(1, 2, 4)]);
+        let yz = array.slice_yz(1).to_darray2d();
+        assert_eq!(yz.dimensions(), (4, 5));
+        assert_eq!(yz[(3, 2)], array[(1, 3, 2)]);
+
+        let xy = array.slice_xy(0).to_darray2d();
+        array.write_slice(Plane::XY, 4, &xy);
+        assert_eq!(array[(2, 3, 4)], array[(2, 3, 0)]);
+        assert!(array.slice_xy(4).iter().eq(array.slice_xy(0).iter()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_region_out_of_bounds() {
+        let mut array = Array3D::new_filled(RuntimeDims::new(4, 4, 4), 0u8);
+        let mut region = array.region_mut(Vec3::zero(), Vec3::new(2, 2, 2));
+        region[(2, 0, 0)] = 1;
+    }
+
+    #[test]
+    #[should_panic(expected = "too short")]
+    fn test_region_mut_checks_storage() {
+        let mut array = Array3D::new_filled(RuntimeDims::new(2, 2, 2), 0u8);
+        // public fields let safe code grow dims past the storage
+        array.dims = RuntimeDims::new(8, 8, 8);
+        array.region_mut(Vec3::zero(), Vec3::new(8, 8, 8))[(7, 7, 7)] = 1;
+    }
+
+    #[test]
+    #[should_panic(expected = "out of storage")]
+    fn test_region_mut_checks_wrapped_dims() {
+        let mut array = Array3D::new_filled(WrappedDims, 0u8);
+        assert!(array.data.is_empty());
+        array.region_mut(Vec3::zero(), Vec3::new(6, 1, 1))[(5, 0, 0)] = 1;
+    }
+}
@@ -1,16 +1,24 @@
 use crate::array3d::Dim3;
+use std::hash::{Hash, Hasher};
 use std::ops::{BitAnd, BitAndAssign, BitOrAssign, Not, Shl, Shr};
 
-/// 3D array, but each element is a boolean
-#[derive(Debug)]
-pub struct BitArray3d<T, D: Dim3> {
-    pub data: Box<[T]>,
-    pub dims: D,
+/// Storage word of packed bits. Implemented for every type with the needed operations (`u8`..`u128`).
+pub trait BitWord:
+    Default
+    + Copy
+    + BitAnd<Output = Self>
+    + BitOrAssign
+    + BitAndAssign
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+    + Not<Output = Self>
+    + PartialEq
+    + From<u8>
+{
+    const BITS: usize = std::mem::size_of::<Self>() * 8;
 }
 
-impl<T, D> BitArray3d<T, D>
-where
-    D: Dim3,
+impl<T> BitWord for T where
     T: Default
         + Copy
         + BitAnd<Output = T>
@@ -20,13 +28,121 @@ where
         + Shr<usize, Output = T>
         + Not<Output = T>
         + PartialEq
-        + From<u8>,
+        + From<u8>
 {
-    const BITS: usize = std::mem::size_of::<T>() * 8;
+}
+
+/// Number of words needed for `bits` packed bits.
+pub(crate) fn words_for<T: BitWord>(bits: usize) -> usize {
+    bits.div_ceil(T::BITS)
+}
+
+/// Word index and mask of bit `pos`.
+fn locate<T: BitWord>(pos: usize) -> (usize, T) {
+    let one: T = 1_u8.into();
+    (pos / T::BITS, one << (pos % T::BITS))
+}
+
+/// Bit `pos` of packed `data`, `false` past the end.
+pub(crate) fn get_bit<T: BitWord>(data: &[T], pos: usize) -> bool {
+    let (chunk, mask) = locate::<T>(pos);
+    data.get(chunk).is_some_and(|&word| (word & mask) != T::default())
+}
+
+/// Sets bit `pos` of packed `data`, ignored past the end.
+pub(crate) fn set_bit<T: BitWord>(data: &mut [T], pos: usize, value: bool) {
+    let (chunk, mask) = locate::<T>(pos);
+    if let Some(word) = data.get_mut(chunk) {
+        if value {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+    }
+}
 
+/// Word with the low `bits` bits set, `bits <= T::BITS`.
+fn low_mask<T: BitWord>(bits: usize) -> T {
+    if bits == T::BITS {
+        !T::default()
+    } else {
+        !(!T::default() << bits)
+    }
+}
+
+/// Splits the `width`-bit field at bit `offset` into its parts inside each word.
+/// Yields (word index, first bit in word, bit count, first bit in field).
+fn field_parts<T: BitWord>(
+    offset: usize,
+    width: usize,
+) -> impl Iterator<Item = (usize, usize, usize, usize)> {
+    debug_assert!(width <= usize::BITS as usize, "field wider than usize");
+    let mut done = 0;
+    std::iter::from_fn(move || {
+        if done == width {
+            return None;
+        }
+        let pos = offset + done;
+        let (word, bit) = (pos / T::BITS, pos % T::BITS);
+        let count = (T::BITS - bit).min(width - done);
+        let part = (word, bit, count, done);
+        done += count;
+        Some(part)
+    })
+}
+
+/// `width`-bit unsigned field starting at bit `offset` of packed `data`, lowest bit first.
+/// Bits past the end read as 0.
+pub(crate) fn get_bits<T: BitWord + TryInto<usize>>(
+    data: &[T],
+    offset: usize,
+    width: usize,
+) -> usize {
+    field_parts::<T>(offset, width).fold(0, |field, (word, bit, count, shift)| {
+        let Some(&word) = data.get(word) else {
+            return field;
+        };
+        // at most `count` <= usize::BITS bits, always fits
+        let part: usize = ((word >> bit) & low_mask(count)).try_into().ok().unwrap();
+        field | part << shift
+    })
+}
+
+/// Writes the low `width` bits of `value` as a field starting at bit `offset` of packed `data`.
+/// Bits past the end are ignored.
+pub(crate) fn set_bits<T: BitWord + TryFrom<usize>>(
+    data: &mut [T],
+    offset: usize,
+    width: usize,
+    value: usize,
+) {
+    for (word, bit, count, shift) in field_parts::<T>(offset, width) {
+        let Some(word) = data.get_mut(word) else {
+            return;
+        };
+        // at most `count` <= T::BITS bits, always fits
+        let part = (value >> shift) & low_mask::<usize>(count);
+        let part: T = part.try_into().ok().unwrap();
+        let mask = low_mask::<T>(count) << bit;
+        *word &= !mask;
+        *word |= part << bit;
+    }
+}
+
+/// 3D array, but each element is a boolean
+#[derive(Debug, Clone)]
+pub struct BitArray3d<T, D: Dim3> {
+    pub data: Box<[T]>,
+    pub dims: D,
+}
+
+impl<T, D> BitArray3d<T, D>
+where
+    D: Dim3,
+    T: BitWord,
+{
     pub fn new(dims: D) -> Self {
-        let total_bits = dims.total_len();
-        let total_chunks = total_bits.div_ceil(Self::BITS);
+        let total_chunks = words_for::<T>(dims.total_len());
 
         Self {
             dims,
@@ -35,8 +151,7 @@ where
     }
 
     pub fn new_filled(dims: D, value: bool) -> Self {
-        let total_bits = dims.total_len();
-        let total_chunks = total_bits.div_ceil(Self::BITS);
+        let total_chunks = words_for::<T>(dims.total_len());
         let fill_value = if value { !T::default() } else { T::default() };
 
         Self {
@@ -62,54 +177,36 @@ where
     }
 
     pub fn get(&self, x: usize, y: usize, z: usize) -> bool {
-        let pos = self.linear_index(x, y, z);
-        let chunk = pos / Self::BITS;
-        let bit = pos % Self::BITS;
-
-        if chunk >= self.data.len() {
-            return false;
-        }
-
-        let one: T = 1_u8.into();
-        let mask = one << bit;
-
-        (self.data[chunk] & mask) != T::default()
+        get_bit(&self.data, self.linear_index(x, y, z))
     }
 
     pub unsafe fn get_unchecked(&self, x: usize, y: usize, z: usize) -> bool {
         let pos = self.linear_index(x, y, z);
-        let chunk = pos / Self::BITS;
-        let bit = pos % Self::BITS;
-        let one: T = 1_u8.into();
-        let mask = one << bit;
+        let (chunk, mask) = locate::<T>(pos);
         (*unsafe { self.data.get_unchecked(chunk) } & mask) != T::default()
     }
 
     pub fn set(&mut self, x: usize, y: usize, z: usize, value: bool) {
         let pos = self.linear_index(x, y, z);
-        let chunk = pos / Self::BITS;
-        let bit = pos % Self::BITS;
-
-        if chunk >= self.data.len() {
-            return;
-        }
-
-        let one: T = 1_u8.into();
-        let mask = one << bit;
+        set_bit(&mut self.data, pos, value);
+    }
 
-        if value {
-            self.data[chunk] |= mask;
-        } else {
-            self.data[chunk] &= !mask;
-        }
+    /// Storage chunks, with bits past the last element cleared (`new_filled` and `fill` set them).
+    fn chunks_masked(&self) -> impl Iterator<Item = T> + '_ {
+        let trailing = self.dims.total_len() % T::BITS;
+        let last = self.data.len().wrapping_sub(1);
+        self.data.iter().enumerate().map(move |(i, &chunk)| {
+            if i == last && trailing != 0 {
+                chunk & !(!T::default() << trailing)
+            } else {
+                chunk
+            }
+        })
     }
 
     pub unsafe fn set_unchecked(&mut self, x: usize, y: usize, z: usize, value: bool) {
         let pos = self.linear_index(x, y, z);
-        let chunk = pos / Self::BITS;
-        let bit = pos % Self::BITS;
-        let one: T = 1_u8.into();
-        let mask = one << bit;
+        let (chunk, mask) = locate::<T>(pos);
         let slot = unsafe { self.data.get_unchecked_mut(chunk) };
         if value {
             *slot |= mask;
@@ -118,3 +215,83 @@ where
         }
     }
 }
+
+/// Compares dims and bits, storage past the last bit is ignored.
+impl<T, D> PartialEq for BitArray3d<T, D>
+where
+    D: Dim3,
+    T: BitWord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.dimensions() == other.dimensions() && self.chunks_masked().eq(other.chunks_masked())
+    }
+}
+
+impl<T, D> Eq for BitArray3d<T, D>
+where
+    D: Dim3,
+    T: BitWord + Eq,
+{
+}
+
+impl<T, D> Hash for BitArray3d<T, D>
+where
+    D: Dim3,
+    T: BitWord + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dimensions().hash(state);
+        self.chunks_masked().for_each(|chunk| chunk.hash(state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array3d::RuntimeDims;
+
+    #[test]
+    fn test_equality_ignores_trailing_bits() {
+        let dims = RuntimeDims::new(3, 3, 3);
+        let filled = BitArray3d::<u8, _>::new_filled(dims, true);
+        let mut set = BitArray3d::<u8, _>::new(dims);
+        for z in 0..3 {
+            for y in 0..3 {
+                for x in 0..3 {
+                    set.set(x, y, z, true);
+                }
+            }
+        }
+        assert_ne!(filled.data, set.data);
+        assert_eq!(filled, set);
+
+        let mut copy = filled.clone();
+        copy.set(2, 2, 2, false);
+        assert_ne!(copy, filled);
+    }
+
+    #[test]
+    fn test_fields_across_words() {
+        let mut words = [0u64; 3];
+        // 13-bit fields, some cross a word boundary
+        for i in 0..(192 / 13) {
+            set_bits(&mut words, i * 13, 13, 0x1A5F ^ i);
+        }
+        for i in 0..(192 / 13) {
+            assert_eq!(get_bits(&words, i * 13, 13), 0x1A5F ^ i);
+        }
+        set_bits(&mut words, 60, 64, usize::MAX);
+        assert_eq!(get_bits(&words, 60, 64), usize::MAX);
+        assert_eq!(words[0] >> 60, 0xF);
+        assert_eq!(get_bits(&words, 130, 13), 0x1A5F ^ 10);
+
+        // fields spanning several small words, past the end reads 0 and is not written
+        let mut bytes = [0u8; 3];
+        set_bits(&mut bytes, 3, 20, 0xABCDE);
+        assert_eq!(bytes, [0xF0, 0xE6, 0x55]);
+        assert_eq!(get_bits(&bytes, 3, 20), 0xABCDE);
+        assert_eq!(get_bits(&bytes, 20, 8), 0x5);
+        set_bits(&mut bytes, 20, 8, 0xFF);
+        assert_eq!(bytes, [0xF0, 0xE6, 0xF5]);
+    }
+}
@@ -0,0 +1,313 @@
+// Unbounded sparse grid, made of `CHUNK`³ `Array3D` chunks allocated on first write
+// Addressed by signed world coordinates, split into chunk coordinate + local position with euclidean division.
+
+use crate::array3d::{Array3D, ConstDims};
+use crate::array3d_iter::Positions;
+use qvek::vek::Vec3;
+use std::collections::HashMap;
+
+/// One chunk of a `ChunkedGrid`.
+pub type Chunk<T, const CHUNK: usize> = Array3D<T, ConstDims<CHUNK, CHUNK, CHUNK>>;
+
+/// Sparse grid of `CHUNK`³ chunks. Cells of chunks that are not loaded read as `default`.
+#[derive(Clone, Debug)]
+pub struct ChunkedGrid<T, const CHUNK: usize> {
+    chunks: HashMap<Vec3<i32>, Chunk<T, CHUNK>>,
+    default: T,
+}
+
+impl<T: Clone + Default, const CHUNK: usize> Default for ChunkedGrid<T, CHUNK> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, const CHUNK: usize> ChunkedGrid<T, CHUNK> {
+    const SIZE: i32 = {
+        assert!(
+            CHUNK > 0 && CHUNK <= i32::MAX as usize,
+            "invalid chunk size"
+        );
+        CHUNK as i32
+    };
+
+    /// Creates an empty grid, every cell reads as `default`.
+    pub fn new(default: T) -> Self {
+        Self {
+            chunks: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Value of cells in unloaded chunks.
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    /// Splits world `pos` into chunk coordinate and position inside that chunk.
+    pub fn split(pos: Vec3<i32>) -> (Vec3<i32>, Vec3<usize>) {
+        let chunk = pos.map(|c| c.div_euclid(Self::SIZE));
+        let local = pos.map(|c| c.rem_euclid(Self::SIZE) as usize);
+        (chunk, local)
+    }
+
+    /// World position of the first cell of chunk `coord`.
+    /// `i64`, since the chunk holding `i32::MIN` can start below it.
+    pub fn chunk_origin(coord: Vec3<i32>) -> Vec3<i64> {
+        coord.map(|c| i64::from(c) * i64::from(Self::SIZE))
+    }
+
+    /// World position of `local` in the chunk starting at `origin`, must be a valid `i32` position.
+    fn world(origin: Vec3<i64>, local: Vec3<usize>) -> Vec3<i32> {
+        (origin + local.map(|c| c as i64)).map(|c| c as i32)
+    }
+
+    /// Value at world `pos`, `default` if its chunk is not loaded.
+    pub fn get(&self, pos: Vec3<i32>) -> &T {
+        let (chunk, local) = Self::split(pos);
+        match self.chunks.get(&chunk) {
+            Some(chunk) => chunk.get(local.x, local.y, local.z),
+            None => &self.default,
+        }
+    }
+
+    /// Value at world `pos`, `None` if its chunk is not loaded.
+    pub fn get_loaded_mut(&mut self, pos: Vec3<i32>) -> Option<&mut T> {
+        let (chunk, local) = Self::split(pos);
+        Some(self.chunks.get_mut(&chunk)?.get_mut(local.x, local.y, local.z))
+    }
+
+    pub fn is_loaded(&self, coord: Vec3<i32>) -> bool {
+        self.chunks.contains_key(&coord)
+    }
+
+    pub fn chunk(&self, coord: Vec3<i32>) -> Option<&Chunk<T, CHUNK>> {
+        self.chunks.get(&coord)
+    }
+
+    pub fn chunk_mut(&mut self, coord: Vec3<i32>) -> Option<&mut Chunk<T, CHUNK>> {
+        self.chunks.get_mut(&coord)
+    }
+
+    /// Puts `chunk` at `coord`, returning the chunk it replaced.
+    pub fn insert_chunk(
+        &mut self,
+        coord: Vec3<i32>,
+        chunk: Chunk<T, CHUNK>,
+    ) -> Option<Chunk<T, CHUNK>> {
+        self.chunks.insert(coord, chunk)
+    }
+
+    /// Removes chunk `coord`, its cells read as `default` again.
+    pub fn unload(&mut self, coord: Vec3<i32>) -> Option<Chunk<T, CHUNK>> {
+        self.chunks.remove(&coord)
+    }
+
+    /// Number of loaded chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Iterates (`coord`, `&chunk`) of loaded chunks, in unspecified order.
+    pub fn chunks(&self) -> impl Iterator<Item = (Vec3<i32>, &Chunk<T, CHUNK>)> {
+        self.chunks.iter().map(|(&coord, chunk)| (coord, chunk))
+    }
+
+    /// Iterates (`coord`, `&mut chunk`) of loaded chunks, in unspecified order.
+    pub fn chunks_mut(&mut self) -> impl Iterator<Item = (Vec3<i32>, &mut Chunk<T, CHUNK>)> {
+        self.chunks.iter_mut().map(|(&coord, chunk)| (coord, chunk))
+    }
+
+    /// First and last (inclusive) chunk touched by world box `min..max`, `last < first` if it is empty.
+    fn chunk_range(min: Vec3<i32>, max: Vec3<i32>) -> (Vec3<i32>, Vec3<i32>) {
+        if !max.cmpgt(&min).reduce_and() {
+            return (Vec3::one(), Vec3::zero());
+        }
+        // max > min, so max - 1 can't overflow
+        (Self::split(min).0, Self::split(max - 1).0)
+    }
+
+    /// Chunk coordinates touched by world box `min..max`, x-fastest.
+    fn chunks_in(min: Vec3<i32>, max: Vec3<i32>) -> impl Iterator<Item = Vec3<i32>> {
+        let (first, last) = Self::chunk_range(min, max);
+        (first.z..=last.z).flat_map(move |z| {
+            (first.y..=last.y)
+                .flat_map(move |y| (first.x..=last.x).map(move |x| Vec3::new(x, y, z)))
+        })
+    }
+
+    /// Part of world box `min..max` inside chunk `coord`, in local coordinates.
+    fn local_box(coord: Vec3<i32>, min: Vec3<i32>, max: Vec3<i32>) -> (Vec3<usize>, Vec3<usize>) {
+        let origin = Self::chunk_origin(coord);
+        let size = i64::from(Self::SIZE);
+        let local_min = (min.map(i64::from) - origin).map(|c| c.clamp(0, size));
+        let local_max = (max.map(i64::from) - origin).map2(local_min, |c, lo| c.clamp(lo, size));
+        (local_min.map(|c| c as usize), local_max.map(|c| c as usize))
+    }
+
+    /// Iterates (`world position`, `&T`) of world box `min..max` (`max` exclusive), chunk by chunk.
+    /// Cells of unloaded chunks read as `default`.
+    pub fn iter_region(
+        &self,
+        min: Vec3<i32>,
+        max: Vec3<i32>,
+    ) -> impl Iterator<Item = (Vec3<i32>, &T)> {
+        Self::chunks_in(min, max).flat_map(move |coord| {
+            let origin = Self::chunk_origin(coord);
+            let chunk = self.chunks.get(&coord);
+            let (local_min, local_max) = Self::local_box(coord, min, max);
+            Positions::new(local_min, local_max).map(move |local| {
+                let value = match chunk {
+                    Some(chunk) => chunk.get(local.x, local.y, local.z),
+                    None => &self.default,
+                };
+                (Self::world(origin, local), value)
+            })
+        })
+    }
+}
+
+impl<T: Clone, const CHUNK: usize> ChunkedGrid<T, CHUNK> {
+    /// Chunk `coord`, allocated and filled with `default` if not loaded.
+    pub fn load(&mut self, coord: Vec3<i32>) -> &mut Chunk<T, CHUNK> {
+        let default = &self.default;
+        self.chunks.entry(coord).or_insert_with(|| Array3D::new_filled(ConstDims, default.clone()))
+    }
+
+    /// Mutable reference at world `pos`, loading its chunk.
+    pub fn get_mut(&mut self, pos: Vec3<i32>) -> &mut T {
+        let (chunk, local) = Self::split(pos);
+        self.load(chunk).get_mut(local.x, local.y, local.z)
+    }
+
+    /// Sets value at world `pos`, loading its chunk.
+    pub fn set(&mut self, pos: Vec3<i32>, value: T) {
+        *self.get_mut(pos) = value;
+    }
+
+    /// Iterates (`world position`, `&mut T`) of world box `min..max` (`max` exclusive), chunk by chunk,
+    /// loading every chunk it touches.
+    pub fn iter_region_mut(
+        &mut self,
+        min: Vec3<i32>,
+        max: Vec3<i32>,
+    ) -> impl Iterator<Item = (Vec3<i32>, &mut T)> {
+        for coord in Self::chunks_in(min, max) {
+            self.load(coord);
+        }
+        // pointers are taken after loading, so the map no longer moves chunks around
+        let chunks: Vec<(Vec3<i32>, *mut Chunk<T, CHUNK>)> = Self::chunks_in(min, max)
+            .map(|coord| (coord, self.chunks.get_mut(&coord).unwrap() as *mut _))
+            .collect();
+        chunks.into_iter().flat_map(move |(coord, chunk)| {
+            // SAFETY: coordinates are distinct, so every chunk is borrowed once,
+            // and the map stays mutably borrowed for as long as the iterator lives
+            let chunk = unsafe { &mut *chunk };
+            let origin = Self::chunk_origin(coord);
+            let (local_min, local_max) = Self::local_box(coord, min, max);
+            chunk
+                .indexed_iter_region_mut(local_min, local_max)
+                .map(move |(local, value)| (Self::world(origin, local), value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array3d::number;
+
+    #[test]
+    fn test_lazy_chunks() {
+        let mut grid = ChunkedGrid::<u8, 4>::new(7);
+        assert_eq!(
+            ChunkedGrid::<u8, 4>::split(Vec3::new(-1, 4, -5)),
+            (Vec3::new(-1, 1, -2), Vec3::new(3, 0, 3))
+        );
+        assert_eq!(*grid.get(Vec3::new(-100, 3, 9)), 7);
+        assert_eq!(grid.get_loaded_mut(Vec3::zero()), None);
+        assert_eq!(grid.chunk_count(), 0);
+
+        grid.set(Vec3::new(-1, -1, -1), 1);
+        grid.set(Vec3::new(-4, -2, -3), 2);
+        assert_eq!(grid.chunk_count(), 1);
+        assert!(grid.is_loaded(Vec3::new(-1, -1, -1)));
+        assert_eq!(*grid.get(Vec3::new(-1, -1, -1)), 1);
+        assert_eq!(*grid.get(Vec3::new(-2, -1, -1)), 7);
+        assert_eq!(grid.chunk(Vec3::new(-1, -1, -1)).unwrap()[(3, 3, 3)], 1);
+
+        let chunk = grid.unload(Vec3::new(-1, -1, -1)).unwrap();
+        assert_eq!(*grid.get(Vec3::new(-1, -1, -1)), 7);
+        grid.insert_chunk(Vec3::new(5, 0, 0), chunk);
+        assert_eq!(*grid.get(Vec3::new(23, 3, 3)), 1);
+        assert_eq!(
+            grid.chunks().map(|(coord, _)| coord).collect::<Vec<_>>(),
+            [Vec3::new(5, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_region_across_chunks() {
+        let mut grid = ChunkedGrid::<i32, 4>::default();
+        let (min, max) = (Vec3::new(-3, -1, 2), Vec3::new(3, 2, 6));
+        for (pos, value) in grid.iter_region_mut(min, max) {
+            *value = number(pos);
+        }
+        // x and z cross a chunk boundary, y too (-1 and 0..2)
+        assert_eq!(grid.chunk_count(), 8);
+        assert_eq!(grid.iter_region(min, max).count(), 6 * 3 * 4);
+        assert!(grid.iter_region(min, max).all(|(pos, &v)| v == number(pos)));
+        assert_eq!(*grid.get(Vec3::new(-4, 0, 2)), 0);
+
+        // unloaded chunks read as default, empty boxes touch nothing
+        let outside: Vec<_> =
+            grid.iter_region(Vec3::new(100, 0, 0), Vec3::new(102, 1, 1)).collect();
+        assert_eq!(
+            outside,
+            [(Vec3::new(100, 0, 0), &0), (Vec3::new(101, 0, 0), &0)]
+        );
+        assert_eq!(grid.iter_region_mut(max, min).count(), 0);
+        assert_eq!(grid.chunk_count(), 8);
+    }
+
+    #[test]
+    fn test_region_at_i32_limits() {
+        let mut grid = ChunkedGrid::<u8, 3>::new(0);
+        // chunk of i32::MIN starts below it
+        let (chunk, local) = ChunkedGrid::<u8, 3>::split(Vec3::broadcast(i32::MIN));
+        assert_eq!(
+            ChunkedGrid::<u8, 3>::chunk_origin(chunk),
+            Vec3::broadcast(i32::MIN as i64 - 1)
+        );
+        assert_eq!(local, Vec3::one());
+
+        let (min, max) = (Vec3::broadcast(i32::MIN), Vec3::broadcast(i32::MIN + 4));
+        for (_, value) in grid.iter_region_mut(min, max) {
+            *value = 1;
+        }
+        assert_eq!(
+            grid.iter_region(min, max).filter(|&(_, &v)| v == 1).count(),
+            64
+        );
+        assert_eq!(grid.iter_region(min, max).next().unwrap().0, min);
+
+        let (min, max) = (Vec3::broadcast(i32::MAX - 4), Vec3::broadcast(i32::MAX));
+        for (pos, value) in grid.iter_region_mut(min, max) {
+            *value = (pos.x - min.x) as u8;
+        }
+        assert!(grid.iter_region(min, max).all(|(pos, &v)| v == (pos.x - min.x) as u8));
+        assert_eq!(*grid.get(Vec3::broadcast(i32::MAX)), 0);
+
+        // empty boxes at the limits touch nothing
+        let count = grid.chunk_count();
+        assert_eq!(
+            grid.iter_region_mut(Vec3::zero(), Vec3::broadcast(i32::MIN)).count(),
+            0
+        );
+        assert_eq!(
+            grid.iter_region(Vec3::broadcast(i32::MAX), Vec3::broadcast(i32::MAX)).count(),
+            0
+        );
+        assert_eq!(grid.chunk_count(), count);
+    }
+}
@@ -0,0 +1,335 @@
+// Arena that can be allocated into from many threads at once (e.g. from Multiprocessor workers)
+// Storage is split into buckets of doubling size that never move, so references returned by `get`
+// stay valid while other threads keep allocating. Freeing requires `&mut self`.
+
+use crate::arena::Handle;
+use std::{
+    cell::UnsafeCell,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicU8, AtomicU32, AtomicUsize, Ordering},
+};
+
+/// log2 of the first bucket size.
+const FIRST_BUCKET_BITS: u32 = 5;
+const FIRST_BUCKET_LEN: usize = 1 << FIRST_BUCKET_BITS;
+/// Enough buckets to cover every index.
+const BUCKETS: usize = (usize::BITS - FIRST_BUCKET_BITS) as usize;
+
+/// Terminates the free list.
+const NO_FREE: usize = usize::MAX;
+
+const VACANT: u8 = 0;
+const READY: u8 = 1;
+
+struct Slot<T> {
+    /// `READY` once `value` is initialized (Release on write, Acquire on read).
+    state: AtomicU8,
+    /// Only changed under `&mut`, atomic just to be shareable.
+    generation: AtomicU32,
+    /// Next free slot, valid while the slot is in the free list. Only changed under `&mut`.
+    next_free: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    fn new() -> Self {
+        Self {
+            state: AtomicU8::new(VACANT),
+            generation: AtomicU32::new(0),
+            next_free: AtomicUsize::new(NO_FREE),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+impl<T> Drop for Slot<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == READY {
+            unsafe { self.value.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+/// Returns (bucket, offset in bucket) for a slot index.
+fn locate(index: usize) -> Option<(usize, usize)> {
+    let biased = index.checked_add(FIRST_BUCKET_LEN)?;
+    let bucket = (usize::BITS - 1 - biased.leading_zeros() - FIRST_BUCKET_BITS) as usize;
+    Some((bucket, biased - bucket_len(bucket)))
+}
+
+fn bucket_len(bucket: usize) -> usize {
+    FIRST_BUCKET_LEN << bucket
+}
+
+/// Arena supporting `allocate(&self)` and `get(&self)` from many threads.
+///
+/// Allocation is lock-free: fresh slots come from an atomic counter, and freed slots
+/// are popped from a free list which only `&mut self` methods push to (so no ABA).
+pub struct ConcurrentArena<T> {
+    buckets: [AtomicPtr<Slot<T>>; BUCKETS],
+    /// Next never-used slot index.
+    next: AtomicUsize,
+    free_head: AtomicUsize,
+    /// Number of live objects.
+    len: AtomicUsize,
+    _phantom: PhantomData<T>,
+}
+
+// values are moved in from any thread and read by reference from any thread
+unsafe impl<T: Send> Send for ConcurrentArena<T> {}
+unsafe impl<T: Send + Sync> Sync for ConcurrentArena<T> {}
+
+impl<T> Default for ConcurrentArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ConcurrentArena<T> {
+    pub fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicPtr::new(ptr::null_mut())),
+            next: AtomicUsize::new(0),
+            free_head: AtomicUsize::new(NO_FREE),
+            len: AtomicUsize::new(0),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns slot at `index` if its bucket was allocated.
+    fn slot(&self, index: usize) -> Option<&Slot<T>> {
+        let (bucket, offset) = locate(index)?;
+        let base = self.buckets[bucket].load(Ordering::Acquire);
+        if base.is_null() {
+            return None;
+        }
+        Some(unsafe { &*base.add(offset) })
+    }
+
+    /// Returns slot at `index`, allocating its bucket if needed.
+    fn slot_or_alloc(&self, index: usize) -> &Slot<T> {
+        let (bucket, offset) = locate(index).expect("concurrent arena index overflow");
+        let mut base = self.buckets[bucket].load(Ordering::Acquire);
+        if base.is_null() {
+            let new: Box<[Slot<T>]> = (0..bucket_len(bucket)).map(|_| Slot::new()).collect();
+            let new = Box::into_raw(new) as *mut Slot<T>;
+            match self.buckets[bucket].compare_exchange(
+                ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => base = new,
+                Err(existing) => {
+                    // another thread won the race, ours was never shared
+                    drop(unsafe {
+                        Box::from_raw(ptr::slice_from_raw_parts_mut(new, bucket_len(bucket)))
+                    });
+                    base = existing;
+                }
+            }
+        }
+        unsafe { &*base.add(offset) }
+    }
+
+    fn pop_free(&self) -> Option<usize> {
+        let mut head = self.free_head.load(Ordering::Acquire);
+        loop {
+            if head == NO_FREE {
+                return None;
+            }
+            // slots in the free list always have their bucket allocated
+            let next = self.slot(head).unwrap().next_free.load(Ordering::Relaxed);
+            match self.free_head.compare_exchange_weak(
+                head,
+                next,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(head),
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Allocates a new object in the arena, returning its index. Can be called from many threads.
+    pub fn allocate(&self, value: T) -> usize {
+        let index = self
+            .pop_free()
+            .unwrap_or_else(|| self.next.fetch_add(1, Ordering::Relaxed));
+        let slot = self.slot_or_alloc(index);
+        // index is exclusively ours until published
+        unsafe { (*slot.value.get()).write(value) };
+        slot.state.store(READY, Ordering::Release);
+        self.len.fetch_add(1, Ordering::Relaxed);
+        index
+    }
+
+    /// Allocates a new object in the arena, returning a generation-checked handle.
+    pub fn insert(&self, value: T) -> Handle<T> {
+        let index = self.allocate(value);
+        let generation = self.slot(index).unwrap().generation.load(Ordering::Relaxed);
+        Handle::new(index, generation)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let slot = self.slot(index)?;
+        if slot.state.load(Ordering::Acquire) != READY {
+            return None;
+        }
+        Some(unsafe { (*slot.value.get()).assume_init_ref() })
+    }
+
+    pub fn get_handle(&self, handle: Handle<T>) -> Option<&T> {
+        let slot = self.slot(handle.index())?;
+        if slot.generation.load(Ordering::Relaxed) != handle.generation() {
+            return None;
+        }
+        self.get(handle.index())
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let slot = self.slot(index)?;
+        if slot.state.load(Ordering::Relaxed) != READY {
+            return None;
+        }
+        Some(unsafe { (*slot.value.get()).assume_init_mut() })
+    }
+
+    /// Removes the object at `index`, its slot gets reused by later allocations.
+    pub fn take(&mut self, index: usize) -> Option<T> {
+        let slot = self.slot(index)?;
+        if slot.state.load(Ordering::Relaxed) != READY {
+            return None;
+        }
+        slot.state.store(VACANT, Ordering::Relaxed);
+        slot.generation.fetch_add(1, Ordering::Relaxed);
+        slot.next_free.store(self.free_head.load(Ordering::Relaxed), Ordering::Relaxed);
+        let value = unsafe { (*slot.value.get()).assume_init_read() };
+        self.free_head.store(index, Ordering::Relaxed);
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        Some(value)
+    }
+
+    /// Removes the object `handle` points to. Stale handles are rejected with `None`.
+    pub fn take_handle(&mut self, handle: Handle<T>) -> Option<T> {
+        self.get_handle(handle)?;
+        self.take(handle.index())
+    }
+
+    pub fn free(&mut self, index: usize) {
+        self.take(index);
+    }
+
+    /// Number of live objects.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates (`index`, `&T`) of objects published so far.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        let end = self.next.load(Ordering::Acquire);
+        (0..end).filter_map(|index| Some((index, self.get(index)?)))
+    }
+}
+
+impl<T> Drop for ConcurrentArena<T> {
+    fn drop(&mut self) {
+        for (bucket, base) in self.buckets.iter_mut().enumerate() {
+            let base = *base.get_mut();
+            if !base.is_null() {
+                // slots drop their values
+                drop(unsafe {
+                    Box::from_raw(ptr::slice_from_raw_parts_mut(base, bucket_len(bucket)))
+                });
+            }
+        }
+    }
+}
+
+/// Shows published entries as `index: value`.
+impl<T: Debug> Debug for ConcurrentArena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Allocates every value, indices follow iteration order.
+impl<T> FromIterator<T> for ConcurrentArena<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut arena = Self::default();
+        arena.extend(iter);
+        arena
+    }
+}
+
+impl<T> Extend<T> for ConcurrentArena<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.allocate(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        assert_eq!(locate(0), Some((0, 0)));
+        assert_eq!(locate(FIRST_BUCKET_LEN - 1), Some((0, FIRST_BUCKET_LEN - 1)));
+        assert_eq!(locate(FIRST_BUCKET_LEN), Some((1, 0)));
+        assert_eq!(locate(3 * FIRST_BUCKET_LEN), Some((2, 0)));
+        assert_eq!(locate(usize::MAX), None);
+    }
+
+    #[test]
+    fn test_parallel_allocate() {
+        let arena = ConcurrentArena::new();
+        let per_thread = 1000;
+        let threads = 4;
+        std::thread::scope(|s| {
+            for t in 0..threads {
+                let arena = &arena;
+                s.spawn(move || {
+                    for i in 0..per_thread {
+                        let index = arena.allocate(t * per_thread + i);
+                        assert_eq!(arena.get(index), Some(&(t * per_thread + i)));
+                    }
+                });
+            }
+        });
+        assert_eq!(arena.len(), threads * per_thread);
+        let mut values: Vec<_> = arena.iter().map(|(_, v)| *v).collect();
+        values.sort();
+        assert_eq!(values, (0..threads * per_thread).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_free_and_reuse() {
+        let mut arena = ConcurrentArena::new();
+        let a = arena.insert(String::from("a"));
+        let _b = arena.insert(String::from("b"));
+        assert_eq!(arena.take_handle(a).as_deref(), Some("a"));
+        assert_eq!(arena.get_handle(a), None);
+
+        let c = arena.insert(String::from("c"));
+        assert_eq!(c.index(), a.index());
+        assert_eq!(arena.get_handle(c).map(String::as_str), Some("c"));
+        assert_eq!(arena.take_handle(a), None);
+        assert_eq!(arena.len(), 2);
+
+        arena.extend(["d".to_string()]);
+        assert_eq!(format!("{arena:?}"), r#"{0: "c", 1: "b", 2: "d"}"#);
+        let collected: ConcurrentArena<_> = (0..100).collect();
+        assert_eq!(collected.get(99), Some(&99));
+    }
+}
@@ -1,12 +1,12 @@
 #![allow(unused_imports)]
 use qvek::vek::Vec3;
 
-use crate::{Array3D, array3d::RuntimeDims};
+use crate::{Array3D, Linear, array3d::RuntimeDims};
 use std::fmt::{self, Debug};
 use std::ops::{Index, IndexMut};
 
 /// Dynamic 3D array wrapper specialized for runtime dimensions.
-pub type DArray3D<T> = Array3D<T, RuntimeDims>;
+pub type DArray3D<T, L = Linear> = Array3D<T, RuntimeDims, L>;
 
 // impl<T> DArray3D<T> {
 //     /// Create an array with all elements cloned from `value`.
@@ -0,0 +1,278 @@
+// Sparse-set flavour of `Arena`: values are packed in a dense Vec, slots only store where their value lives
+// Iteration is O(live) and values are contiguous, freeing swap-removes. Slot indices and handles stay stable.
+
+use crate::arena::Handle;
+use std::fmt::{self, Debug};
+use std::ops::{Index, IndexMut};
+
+/// Terminates the free list.
+const NO_FREE: usize = usize::MAX;
+
+#[derive(Clone)]
+struct DenseSlot {
+    /// Bumped every time the slot is freed.
+    generation: u32,
+    /// Position in `values` when occupied, next free slot otherwise.
+    target: usize,
+    occupied: bool,
+}
+
+#[derive(Clone)]
+pub struct DenseArena<T> {
+    /// Live values, packed.
+    values: Vec<T>,
+    /// Slot index owning each value in `values`.
+    owners: Vec<usize>,
+    slots: Vec<DenseSlot>,
+    /// Head of the intrusive free list.
+    free_head: usize,
+}
+
+impl<T> Default for DenseArena<T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> DenseArena<T> {
+    /// Creates an arena with room for `initial_size` values.
+    pub fn new(initial_size: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(initial_size),
+            owners: Vec::with_capacity(initial_size),
+            slots: Vec::with_capacity(initial_size),
+            free_head: NO_FREE,
+        }
+    }
+
+    /// Allocates a new object in the arena, returning its (stable) slot index.
+    pub fn allocate(&mut self, value: T) -> usize {
+        let dense = self.values.len();
+        let index = if self.free_head != NO_FREE {
+            let index = self.free_head;
+            let slot = &mut self.slots[index];
+            self.free_head = slot.target;
+            slot.target = dense;
+            slot.occupied = true;
+            index
+        } else {
+            self.slots.push(DenseSlot {
+                generation: 0,
+                target: dense,
+                occupied: true,
+            });
+            self.slots.len() - 1
+        };
+        self.values.push(value);
+        self.owners.push(index);
+        index
+    }
+
+    /// Allocates a new object in the arena, returning a generation-checked handle.
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        let index = self.allocate(value);
+        Handle::new(index, self.slots[index].generation)
+    }
+
+    /// Position of the value of slot `index` in `as_slice()`.
+    fn dense_index(&self, index: usize) -> Option<usize> {
+        let slot = self.slots.get(index)?;
+        slot.occupied.then_some(slot.target)
+    }
+
+    fn dense_index_of(&self, handle: Handle<T>) -> Option<usize> {
+        let slot = self.slots.get(handle.index())?;
+        (slot.occupied && slot.generation == handle.generation()).then_some(slot.target)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        Some(&self.values[self.dense_index(index)?])
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let dense = self.dense_index(index)?;
+        Some(&mut self.values[dense])
+    }
+
+    /// Removes the object at `index`. Last value is swapped into its place to keep storage packed.
+    pub fn take(&mut self, index: usize) -> Option<T> {
+        let dense = self.dense_index(index)?;
+        let value = self.values.swap_remove(dense);
+        self.owners.swap_remove(dense);
+        if let Some(&moved) = self.owners.get(dense) {
+            self.slots[moved].target = dense;
+        }
+        let slot = &mut self.slots[index];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.occupied = false;
+        slot.target = self.free_head;
+        self.free_head = index;
+        Some(value)
+    }
+
+    pub fn free(&mut self, index: usize) {
+        self.take(index);
+    }
+
+    /// Creates a handle for the live object at `index`.
+    pub fn handle_of(&self, index: usize) -> Option<Handle<T>> {
+        self.dense_index(index)?;
+        Some(Handle::new(index, self.slots[index].generation))
+    }
+
+    /// Returns `true` if `handle` still points to the object it was created for.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.dense_index_of(handle).is_some()
+    }
+
+    pub fn get_handle(&self, handle: Handle<T>) -> Option<&T> {
+        Some(&self.values[self.dense_index_of(handle)?])
+    }
+
+    pub fn get_handle_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        let dense = self.dense_index_of(handle)?;
+        Some(&mut self.values[dense])
+    }
+
+    /// Removes the object `handle` points to. Stale handles are rejected with `None`.
+    pub fn take_handle(&mut self, handle: Handle<T>) -> Option<T> {
+        self.dense_index_of(handle)?;
+        self.take(handle.index())
+    }
+
+    /// Frees the slot `handle` points to, returning `false` for stale handles.
+    pub fn free_handle(&mut self, handle: Handle<T>) -> bool {
+        self.take_handle(handle).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        for &index in &self.owners {
+            let slot = &mut self.slots[index];
+            slot.generation = slot.generation.wrapping_add(1);
+            slot.occupied = false;
+            slot.target = self.free_head;
+            self.free_head = index;
+        }
+        self.values.clear();
+        self.owners.clear();
+    }
+
+    /// Number of live objects.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Number of slots, live or free.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Live values, packed in unspecified order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Slot index of each value in `as_slice()`.
+    pub fn indices(&self) -> &[usize] {
+        &self.owners
+    }
+
+    /// Iterates (`index`, `&T`) of live entries, in storage order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.owners.iter().copied().zip(self.values.iter())
+    }
+
+    /// Iterates (`index`, `&mut T`) of live entries, in storage order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.owners.iter().copied().zip(self.values.iter_mut())
+    }
+}
+
+/// Panics if `handle` is stale.
+impl<T> Index<Handle<T>> for DenseArena<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &Self::Output {
+        self.get_handle(handle).expect("stale arena handle")
+    }
+}
+
+/// Panics if `handle` is stale.
+impl<T> IndexMut<Handle<T>> for DenseArena<T> {
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut Self::Output {
+        self.get_handle_mut(handle).expect("stale arena handle")
+    }
+}
+
+/// Shows live entries as `index: value`, in storage order.
+impl<T: Debug> Debug for DenseArena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Allocates every value, indices follow iteration order.
+impl<T> FromIterator<T> for DenseArena<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut arena = Self::default();
+        arena.extend(iter);
+        arena
+    }
+}
+
+impl<T> Extend<T> for DenseArena<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.allocate(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_remove_keeps_handles() {
+        let mut arena = DenseArena::new(0);
+        let handles: Vec<_> = (0..5).map(|i| arena.insert(i * 10)).collect();
+
+        assert_eq!(arena.take_handle(handles[1]), Some(10));
+        assert_eq!(arena.as_slice(), &[0, 40, 20, 30]);
+        for (i, &h) in handles.iter().enumerate().filter(|&(i, _)| i != 1) {
+            assert_eq!(arena[h], i * 10);
+        }
+        assert_eq!(arena.get_handle(handles[1]), None);
+
+        // slot reused, stale handle stays stale
+        let h = arena.insert(50);
+        assert_eq!(h.index(), handles[1].index());
+        assert!(!arena.contains(handles[1]));
+
+        let mut seen: Vec<_> = arena.iter().map(|(i, &v)| (i, v)).collect();
+        seen.sort();
+        assert_eq!(seen, vec![(0, 0), (1, 50), (2, 20), (3, 30), (4, 40)]);
+
+        let copy = arena.clone();
+        arena.clear();
+        assert!(arena.is_empty());
+        assert!(!arena.contains(h));
+        assert_eq!(copy[h], 50);
+    }
+
+    #[test]
+    fn test_collect() {
+        let mut arena: DenseArena<_> = ["a", "b"].into_iter().collect();
+        arena.extend(["c"]);
+        assert_eq!(arena.as_slice(), &["a", "b", "c"]);
+        assert_eq!(format!("{arena:?}"), r#"{0: "a", 1: "b", 2: "c"}"#);
+    }
+}
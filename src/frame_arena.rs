@@ -0,0 +1,217 @@
+// Bump allocator for short-lived (e.g. per-frame) data of a single type
+// Values are pushed into chunks that never reallocate, so references stay valid until `reset`.
+// Keep one per frame in flight with `Ring<FrameArena<T>>`.
+
+use crate::Ring;
+use std::cell::RefCell;
+
+/// Smallest chunk allocated when the arena runs out of space.
+const MIN_CHUNK_LEN: usize = 64;
+
+struct Chunks<T> {
+    /// Chunk being bumped into, never pushed past its capacity.
+    current: Vec<T>,
+    /// Full chunks, kept alive until `reset`.
+    full: Vec<Vec<T>>,
+    /// Reused by `alloc_extend` to collect values before they are moved into a chunk.
+    scratch: Vec<T>,
+}
+
+impl<T> Chunks<T> {
+    /// Starts a new chunk with room for at least `additional` values.
+    fn grow(&mut self, additional: usize) {
+        let new_len = (self.current.capacity() * 2).max(additional).max(MIN_CHUNK_LEN);
+        let old = std::mem::replace(&mut self.current, Vec::with_capacity(new_len));
+        if !old.is_empty() {
+            self.full.push(old);
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.current.capacity() - self.current.len()
+    }
+}
+
+/// Typed bump arena handing out `&mut T` / `&mut [T]` that live as long as the arena borrow.
+/// Everything is dropped at once by `reset` (or when the arena is dropped).
+pub struct FrameArena<T> {
+    chunks: RefCell<Chunks<T>>,
+}
+
+impl<T> Default for FrameArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FrameArena<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an arena that fits `capacity` values before allocating another chunk.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            chunks: RefCell::new(Chunks {
+                current: Vec::with_capacity(capacity),
+                full: Vec::new(),
+                scratch: Vec::new(),
+            }),
+        }
+    }
+
+    /// Moves `value` into the arena.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        if chunks.remaining() == 0 {
+            chunks.grow(1);
+        }
+        let index = chunks.current.len();
+        chunks.current.push(value);
+        // chunk never reallocates and is only dropped by `reset(&mut self)`, so element outlives `&self`
+        unsafe { &mut *chunks.current.as_mut_ptr().add(index) }
+    }
+
+    /// Moves every value of `iter` into one contiguous slice of the arena.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_extend(&self, iter: impl IntoIterator<Item = T>) -> &mut [T] {
+        // collect first, so iterator is free to allocate from this arena too
+        // scratch is taken out, a nested call starts from an empty one instead of clobbering it
+        let mut values = std::mem::take(&mut self.chunks.borrow_mut().scratch);
+        values.extend(iter);
+        let mut chunks = self.chunks.borrow_mut();
+        let slice = Self::push_slice(&mut chunks, values.drain(..));
+        chunks.scratch = values;
+        slice
+    }
+
+    /// Clones `values` into one contiguous slice of the arena.
+    /// Cloning must not allocate from this arena (it panics if it does).
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_clone(&self, values: &[T]) -> &mut [T]
+    where
+        T: Clone,
+    {
+        // length is known upfront, so values go straight into the chunk
+        Self::push_slice(&mut self.chunks.borrow_mut(), values.iter().cloned())
+    }
+
+    /// Moves `values` into one contiguous slice of the current chunk, growing it first if needed.
+    fn push_slice<'a>(
+        chunks: &mut Chunks<T>,
+        values: impl ExactSizeIterator<Item = T>,
+    ) -> &'a mut [T] {
+        let len = values.len();
+        if chunks.remaining() < len {
+            chunks.grow(len);
+        }
+        let start = chunks.current.len();
+        // `take` keeps the chunk from reallocating even if `len` was wrong
+        chunks.current.extend(values.take(len));
+        // same as `alloc`
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                chunks.current.as_mut_ptr().add(start),
+                chunks.current.len() - start,
+            )
+        }
+    }
+
+    /// Number of values allocated since last reset.
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.current.len() + chunks.full.iter().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every value at once. Memory is kept, merged into a single chunk
+    /// so the next frame of the same size does not allocate.
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if chunks.full.is_empty() {
+            chunks.current.clear();
+        } else {
+            let total =
+                chunks.current.capacity() + chunks.full.iter().map(Vec::capacity).sum::<usize>();
+            chunks.full.clear();
+            chunks.current = Vec::with_capacity(total);
+        }
+    }
+}
+
+// one bump arena per frame in flight
+impl<T> Ring<FrameArena<T>> {
+    /// Moves to the next frame and resets its arena, dropping what it held from `len()` frames ago.
+    pub fn begin_frame(&mut self) -> &FrameArena<T> {
+        self.move_next();
+        let arena = self.current_mut();
+        arena.reset();
+        arena
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_references_survive_growth() {
+        let arena = FrameArena::new();
+        let first = arena.alloc(0usize);
+        let refs: Vec<&mut usize> = (1..1000).map(|i| arena.alloc(i)).collect();
+        let slice = arena.alloc_extend(0..100);
+        *first = 42;
+        assert_eq!(*first, 42);
+        assert!(refs.iter().enumerate().all(|(i, r)| **r == i + 1));
+        assert_eq!(slice.len(), 100);
+        assert_eq!(slice[99], 99);
+        assert_eq!(arena.len(), 1100);
+    }
+
+    #[test]
+    fn test_alloc_extend_reuses_scratch() {
+        let arena = FrameArena::with_capacity(256);
+        let outer = arena.alloc_extend((0..4).map(|i| {
+            // nested call from inside the iterator gets its own scratch
+            arena.alloc_extend(i * 10..i * 10 + 2)[1]
+        }));
+        assert_eq!(outer, [1, 11, 21, 31]);
+        let capacity = arena.chunks.borrow().scratch.capacity();
+        assert!(capacity >= 4);
+        arena.alloc_extend(0..3);
+        assert_eq!(arena.chunks.borrow().scratch.capacity(), capacity);
+        assert_eq!(arena.alloc_slice_clone(&[5, 6]), [5, 6]);
+        assert_eq!(arena.len(), 8 + 4 + 3 + 2);
+    }
+
+    #[test]
+    fn test_reset_drops_values() {
+        let tracker = Rc::new(());
+        let mut arena = FrameArena::with_capacity(4);
+        for _ in 0..10 {
+            arena.alloc(tracker.clone());
+        }
+        arena.alloc_slice_clone(&[tracker.clone(), tracker.clone()]);
+        assert_eq!(Rc::strong_count(&tracker), 13);
+        arena.reset();
+        assert_eq!(Rc::strong_count(&tracker), 1);
+        assert!(arena.is_empty());
+    }
+
+    #[test]
+    fn test_ring_of_frames() {
+        let tracker = Rc::new(());
+        let mut frames: Ring<FrameArena<Rc<()>>> = Ring::new(2);
+        frames.begin_frame().alloc(tracker.clone());
+        frames.begin_frame().alloc(tracker.clone());
+        assert_eq!(Rc::strong_count(&tracker), 3);
+        // wrapped around to the first frame, its data is gone
+        frames.begin_frame();
+        assert_eq!(Rc::strong_count(&tracker), 2);
+    }
+}
@@ -0,0 +1,146 @@
+// `Array3D` with its elements stored inline instead of in a `Box<[T]>`
+// Size is fixed by const generics, so it can live on the stack, in statics, or be embedded in other structs.
+
+use crate::array3d::{Array3D, ConstDims, Storage};
+use crate::layout::Linear;
+use qvek::vek::Vec3;
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+/// Inline element storage, nested arrays indexed `[z][y][x]` (same order as linear `Array3D`).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InlineStorage<T, const X: usize, const Y: usize, const Z: usize>(pub [[[T; X]; Y]; Z]);
+
+impl<T, const X: usize, const Y: usize, const Z: usize> Deref for InlineStorage<T, X, Y, Z> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.0.as_flattened().as_flattened()
+    }
+}
+
+impl<T, const X: usize, const Y: usize, const Z: usize> DerefMut for InlineStorage<T, X, Y, Z> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.0.as_flattened_mut().as_flattened_mut()
+    }
+}
+
+// SAFETY: always the same flattened view of the nested arrays
+unsafe impl<T, const X: usize, const Y: usize, const Z: usize> Storage<T>
+    for InlineStorage<T, X, Y, Z>
+{
+}
+
+impl<T, const X: usize, const Y: usize, const Z: usize> IntoIterator for InlineStorage<T, X, Y, Z> {
+    type IntoIter = std::iter::Flatten<std::iter::Flatten<std::array::IntoIter<[[T; X]; Y], Z>>>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter().flatten().flatten()
+    }
+}
+
+/// Fixed-size linear `Array3D` stored inline. Has the whole `Array3D` API (views, samplers, stencils...).
+pub type InlineArray3D<T, const X: usize, const Y: usize, const Z: usize> =
+    Array3D<T, ConstDims<X, Y, Z>, Linear, InlineStorage<T, X, Y, Z>>;
+
+impl<T, const X: usize, const Y: usize, const Z: usize> InlineArray3D<T, X, Y, Z> {
+    /// Wraps nested arrays indexed `[z][y][x]`.
+    pub const fn from_nested(data: [[[T; X]; Y]; Z]) -> Self {
+        Self {
+            data: InlineStorage(data),
+            dims: ConstDims,
+            layout: Linear,
+            element: PhantomData,
+        }
+    }
+
+    /// Creates array via a position-aware generator.
+    pub fn from_fn_pos_inline(mut generator: impl FnMut(Vec3<usize>) -> T) -> Self {
+        Self::from_nested(std::array::from_fn(|z| {
+            std::array::from_fn(|y| std::array::from_fn(|x| generator(Vec3::new(x, y, z))))
+        }))
+    }
+}
+
+impl<T: Copy, const X: usize, const Y: usize, const Z: usize> InlineArray3D<T, X, Y, Z> {
+    /// Creates a new array with all elements set to `value`.
+    pub const fn new_filled_inline(value: T) -> Self {
+        Self::from_nested([[[value; X]; Y]; Z])
+    }
+}
+
+impl<T: Clone, const X: usize, const Y: usize, const Z: usize> InlineArray3D<T, X, Y, Z> {
+    /// Heap-allocated copy.
+    pub fn to_array3d(&self) -> Array3D<T, ConstDims<X, Y, Z>> {
+        Array3D::from_storage(ConstDims, (*self.data).into())
+    }
+}
+
+impl<T: Clone, const X: usize, const Y: usize, const Z: usize> From<&Array3D<T, ConstDims<X, Y, Z>>>
+    for InlineArray3D<T, X, Y, Z>
+{
+    fn from(array: &Array3D<T, ConstDims<X, Y, Z>>) -> Self {
+        Self::from_fn_pos_inline(|pos| array.get(pos.x, pos.y, pos.z).clone())
+    }
+}
+
+impl<T: Default, const X: usize, const Y: usize, const Z: usize> Default
+    for InlineArray3D<T, X, Y, Z>
+{
+    fn default() -> Self {
+        Self::from_fn_pos_inline(|_| T::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array3d::number;
+    use crate::sampler::EdgeMode;
+
+    // built at compile time, no allocation
+    static LUT: InlineArray3D<u8, 2, 2, 2> = {
+        let mut lut = InlineArray3D::new_filled_inline(1);
+        lut.data.0[1][1][1] = 8;
+        lut
+    };
+
+    #[test]
+    fn test_inline_access() {
+        assert_eq!(LUT[(1, 1, 1)], 8);
+        assert_eq!(LUT.try_get(2, 0, 0), None);
+        assert_eq!(LUT.get_signed(Vec3::new(-1, 0, 0)), None);
+        assert_eq!(size_of::<InlineArray3D<u8, 2, 2, 2>>(), 8);
+
+        let mut array = InlineArray3D::<usize, 3, 2, 2>::from_fn_pos_inline(number);
+        assert_eq!(array.data[4], 11);
+        array[(2, 1, 0)] = 0;
+        assert!(array.indexed_iter().all(|(p, &v)| v == 0 || v == number(p)));
+
+        let boxed = array.to_array3d();
+        assert!(boxed.iter().eq(array.iter()));
+        assert_eq!(InlineArray3D::from(&boxed), array);
+        assert!(array.into_iter().eq(boxed));
+    }
+
+    #[test]
+    fn test_inline_shares_array3d_api() {
+        let mut array = InlineArray3D::<u8, 4, 4, 2>::default();
+        array.region_mut(Vec3::new(1, 1, 0), Vec3::new(3, 3, 1)).fill(5);
+        assert_eq!(
+            array.region(Vec3::zero(), Vec3::new(4, 4, 1)).iter().filter(|&&v| v == 5).count(),
+            4
+        );
+
+        let sampler = array.sampler(EdgeMode::Clamp);
+        assert_eq!(*sampler.get(Vec3::new(-3, 1, 9)), 0);
+        assert_eq!(*sampler.get(Vec3::new(2, 2, -1)), 5);
+
+        // stencils keep the inline storage
+        let grown: InlineArray3D<u8, 4, 4, 2> = array.dilate(1, EdgeMode::Clamp);
+        assert_eq!(grown.iter().filter(|&&v| v == 5).count(), 32);
+        assert_eq!(grown.as_view::<()>()[(0, 0, 0)], 5);
+    }
+}
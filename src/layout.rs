@@ -0,0 +1,158 @@
+use crate::array3d::Dim3;
+
+/// Storage order of an `Array3D`, mapping (x, y, z) to a position in its flat data.
+/// Layouts may pad storage, so `data.len()` can exceed `dims.total_len()`.
+///
+/// # Safety
+/// `index` must map every in-bounds position to a distinct value below `storage_len`.
+/// Mutable views and iterators rely on it to hand out non-aliasing references.
+pub unsafe trait Layout: Copy + Clone + Default {
+    /// Number of storage elements needed for `dims` (including padding).
+    fn storage_len<D: Dim3>(dims: &D) -> usize;
+
+    /// Storage position of (x, y, z). Coordinates must be within `dims`.
+    fn index<D: Dim3>(dims: &D, x: usize, y: usize, z: usize) -> usize;
+}
+
+/// Row-major, x-fastest order (`x + y * X + z * X * Y`). No padding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Linear;
+
+unsafe impl Layout for Linear {
+    fn storage_len<D: Dim3>(dims: &D) -> usize {
+        dims.total_len()
+    }
+
+    fn index<D: Dim3>(dims: &D, x: usize, y: usize, z: usize) -> usize {
+        x + y * dims.x() + z * dims.x() * dims.y()
+    }
+}
+
+/// Morton (Z-order) curve, bits of x, y and z interleaved, so neighbours in any direction are mostly close in memory.
+/// Only the low bits all three axes share are interleaved: the array is cut into cubes as big as
+/// the shortest axis (rounded up to a power of two), Morton order inside each cube, cubes stored linearly.
+/// Exact for cubic power-of-two dims, never pads past the product of dims rounded up to powers of two.
+/// Supports cubes up to 2^21.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Morton;
+
+impl Morton {
+    /// Spreads lower 21 bits of `v` so there are two zero bits between each.
+    fn spread(v: usize) -> u64 {
+        let mut v = v as u64 & 0x1f_ffff;
+        v = (v | v << 32) & 0x001f_0000_0000_ffff;
+        v = (v | v << 16) & 0x001f_0000_ff00_00ff;
+        v = (v | v << 8) & 0x100f_00f0_0f00_f00f;
+        v = (v | v << 4) & 0x10c3_0c30_c30c_30c3;
+        v = (v | v << 2) & 0x1249_2492_4924_9249;
+        v
+    }
+
+    fn encode(x: usize, y: usize, z: usize) -> usize {
+        (Self::spread(x) | Self::spread(y) << 1 | Self::spread(z) << 2) as usize
+    }
+
+    /// Side of the Morton-ordered cubes, the shortest axis rounded up to a power of two.
+    fn cube_side<D: Dim3>(dims: &D) -> usize {
+        dims.x().min(dims.y()).min(dims.z()).next_power_of_two()
+    }
+}
+
+unsafe impl Layout for Morton {
+    fn storage_len<D: Dim3>(dims: &D) -> usize {
+        if dims.x() == 0 || dims.y() == 0 || dims.z() == 0 {
+            return 0;
+        }
+        let side = Self::cube_side(dims);
+        assert!(side <= 1 << 21, "Morton layout supports cubes up to 2^21");
+        // side divides every axis rounded up to a power of two, so this stays within their product
+        let cubes = [dims.x(), dims.y(), dims.z()].map(|n| n.div_ceil(side));
+        cubes
+            .into_iter()
+            .try_fold(side * side * side, usize::checked_mul)
+            .expect("Morton storage overflows usize")
+    }
+
+    fn index<D: Dim3>(dims: &D, x: usize, y: usize, z: usize) -> usize {
+        let side = Self::cube_side(dims);
+        let bits = side.trailing_zeros();
+        let (cubes_x, cubes_y) = (dims.x().div_ceil(side), dims.y().div_ceil(side));
+        let cube = (x >> bits) + (y >> bits) * cubes_x + (z >> bits) * cubes_x * cubes_y;
+        let mask = side - 1;
+        cube << (3 * bits) | Self::encode(x & mask, y & mask, z & mask)
+    }
+}
+
+/// Bricks of `B`³ elements stored one after another (x-fastest), linear order inside each brick.
+/// Pads every axis up to a multiple of `B`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bricked<const B: usize>;
+
+/// 8³ bricks, one brick of `u8` is 512 bytes.
+pub type Brick8 = Bricked<8>;
+
+unsafe impl<const B: usize> Layout for Bricked<B> {
+    fn storage_len<D: Dim3>(dims: &D) -> usize {
+        dims.x().div_ceil(B) * dims.y().div_ceil(B) * dims.z().div_ceil(B) * B * B * B
+    }
+
+    fn index<D: Dim3>(dims: &D, x: usize, y: usize, z: usize) -> usize {
+        let bricks_x = dims.x().div_ceil(B);
+        let bricks_y = dims.y().div_ceil(B);
+        let brick = x / B + (y / B) * bricks_x + (z / B) * bricks_x * bricks_y;
+        let local = x % B + (y % B) * B + (z % B) * B * B;
+        brick * B * B * B + local
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array3d::RuntimeDims;
+
+    fn assert_bijective<L: Layout>(dims: RuntimeDims) {
+        let len = L::storage_len(&dims);
+        assert!(len >= dims.total_len());
+        let mut seen = vec![false; len];
+        for z in 0..dims.z {
+            for y in 0..dims.y {
+                for x in 0..dims.x {
+                    let index = L::index(&dims, x, y, z);
+                    assert!(!seen[index], "{x} {y} {z} aliases another cell");
+                    seen[index] = true;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_layouts_bijective() {
+        for dims in [
+            RuntimeDims::new(1, 1, 1),
+            RuntimeDims::new(8, 8, 8),
+            RuntimeDims::new(5, 3, 7),
+            RuntimeDims::new(17, 9, 2),
+            RuntimeDims::new(4, 4, 37),
+            RuntimeDims::new(3, 1, 5),
+        ] {
+            assert_bijective::<Linear>(dims);
+            assert_bijective::<Morton>(dims);
+            assert_bijective::<Brick8>(dims);
+            assert_bijective::<Bricked<3>>(dims);
+        }
+        assert_eq!(
+            Morton::storage_len(&RuntimeDims::new(16, 16, 16)),
+            16 * 16 * 16
+        );
+        // flat and long arrays pad at most to powers of two per axis
+        assert_eq!(
+            Morton::storage_len(&RuntimeDims::new(4, 4, 1000)),
+            4 * 4 * 1000
+        );
+        assert_eq!(Morton::storage_len(&RuntimeDims::new(5, 3, 7)), 8 * 4 * 8);
+        assert_eq!(
+            Morton::storage_len(&RuntimeDims::new(1 << 20, 1, 1)),
+            1 << 20
+        );
+    }
+}
@@ -1,17 +1,38 @@
 pub mod arena;
 pub mod array2d;
 pub mod array3d;
+pub mod array3d_iter;
+mod array3d_par;
+mod array3d_resize;
 pub mod array3d_view;
 pub mod bit_array;
+pub mod chunked_grid;
+pub mod concurrent_arena;
 pub mod darray3d;
+pub mod dense_arena;
+pub mod frame_arena;
+pub mod inline_array3d;
+pub mod layout;
 pub mod multiprocessor;
+pub mod palette_array3d;
 pub mod ring;
+pub mod sampler;
+mod stencil;
 
 pub use arena::*;
 pub use array2d::*;
 pub use array3d::*;
+pub use array3d_iter::*;
 pub use array3d_view::*;
 pub use bit_array::*;
+pub use chunked_grid::*;
+pub use concurrent_arena::*;
 pub use darray3d::*;
+pub use dense_arena::*;
+pub use frame_arena::*;
+pub use inline_array3d::*;
+pub use layout::*;
 pub use multiprocessor::*;
+pub use palette_array3d::*;
 pub use ring::*;
+pub use sampler::*;
@@ -1,4 +1,6 @@
 use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
     sync::{
         atomic::{AtomicBool, AtomicI32, Ordering},
         Arc, Mutex,
@@ -7,6 +9,12 @@ use std::{
     thread::JoinHandle,
 };
 
+/// Task shared by all threads of a dispatch.
+type Task = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// Payload of the first panic of a dispatch, re-raised on the dispatching thread.
+type Panic = Arc<Mutex<Option<Box<dyn Any + Send>>>>;
+
 // Struct representing the thread pool (aka multiprocessor)
 // Why not rayon? Its too slow. $> cargo bench -p lum --bench threads
 pub struct Multiprocessor {
@@ -14,8 +22,11 @@ pub struct Multiprocessor {
     threads: Vec<JoinHandle<()>>,
     threads_active: Arc<AtomicI32>,
     should_stop: Arc<AtomicBool>,
-    current_task: Arc<Mutex<Option<Box<dyn Fn(usize) + Send>>>>,
+    current_task: Arc<Mutex<Option<Task>>>,
+    task_panic: Panic,
     thread_flags: Vec<Arc<AtomicBool>>,
+    /// Held for a whole dispatch, so dispatches from different threads do not interleave.
+    dispatch_lock: Mutex<()>,
 }
 
 impl Default for Multiprocessor {
@@ -28,10 +39,15 @@ impl Multiprocessor {
     pub fn new() -> Self {
         // at least 1 thread, but also leave one for the OS
         let num_threads = std::thread::available_parallelism().map(|n| n.get() - 1).unwrap_or(1);
+        Self::with_threads(num_threads)
+    }
+
+    /// Creates a pool with exactly `num_threads` worker threads.
+    pub fn with_threads(num_threads: usize) -> Self {
         let threads_active = Arc::new(AtomicI32::new(0));
         let should_stop = Arc::new(AtomicBool::new(false));
-        let current_task: Arc<Mutex<Option<Box<dyn Fn(usize) + Send>>>> =
-            Arc::new(Mutex::new(None));
+        let current_task: Arc<Mutex<Option<Task>>> = Arc::new(Mutex::new(None));
+        let task_panic: Panic = Arc::new(Mutex::new(None));
 
         let mut thread_flags = Vec::with_capacity(num_threads);
         let mut threads = Vec::with_capacity(num_threads);
@@ -45,18 +61,28 @@ impl Multiprocessor {
             let threads_active = threads_active.clone();
             let should_stop = should_stop.clone();
             let current_task = current_task.clone();
+            let task_panic = task_panic.clone();
 
             let handle = thread::spawn(move || {
                 // while not stopped explicitly
                 while !should_stop.load(Ordering::Relaxed) {
                     // if corresponding flag is set to do some work
-                    if thread_flag.swap(false, Ordering::Relaxed) {
+                    if thread_flag.swap(false, Ordering::Acquire) {
                         // then do the work and set the flag back to false (work done)
-                        if let Some(task) = current_task.lock().unwrap().as_ref() {
-                            task(i);
+                        // task is cloned out so the lock is not held while working
+                        let task = current_task.lock().unwrap().clone();
+                        if let Some(task) = task {
+                            // caught, so the counter below is always decremented, the caller re-raises it
+                            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| task(i))) {
+                                task_panic
+                                    .lock()
+                                    .unwrap_or_else(|e| e.into_inner())
+                                    .get_or_insert(payload);
+                            }
                         }
                         // also substract one from the counter to let main thread know part of work is done
-                        threads_active.fetch_sub(1, Ordering::Relaxed);
+                        // (task is dropped by now, `dispatch_scoped` relies on it)
+                        threads_active.fetch_sub(1, Ordering::Release);
                     }
                 }
             });
@@ -70,6 +96,8 @@ impl Multiprocessor {
             threads_active,
             should_stop,
             current_task,
+            task_panic,
+            dispatch_lock: Mutex::new(()),
         }
     }
 
@@ -80,12 +108,42 @@ impl Multiprocessor {
     where
         F: Fn(usize) + Send + Sync + 'static,
     {
-        while self.threads_active.load(Ordering::Relaxed) != 0 {}
+        self.run(dispatch_size, Arc::new(func));
+    }
 
-        {
-            let mut task = self.current_task.lock().unwrap();
-            *task = Some(Box::new(func));
+    /// Same as `dispatch`, but `func` may borrow from the caller (like `std::thread::scope`).
+    /// Runs on the calling thread when the pool has no threads.
+    pub fn dispatch_scoped<'env, F>(&self, dispatch_size: usize, func: F)
+    where
+        F: Fn(usize) + Send + Sync + 'env,
+    {
+        if self.num_threads == 0 {
+            (0..dispatch_size).for_each(func);
+            return;
         }
+        let task: Arc<dyn Fn(usize) + Send + Sync + 'env> = Arc::new(func);
+        // SAFETY: `run` holds the dispatch lock until every thread finished and dropped its clone of the task,
+        // then drops the pool's own reference, and only re-raises task panics after that, so nothing outlives 'env
+        let task: Task = unsafe { std::mem::transmute(task) };
+        self.run(dispatch_size, task);
+    }
+
+    /// Runs `task` on the first `dispatch_size` threads and waits for all of them.
+    /// Returns with the task dropped everywhere, and must not panic once workers are started.
+    /// If the task panicked on any thread, the first panic is resumed after that.
+    fn run(&self, dispatch_size: usize, task: Task) {
+        assert!(
+            dispatch_size <= self.num_threads,
+            "dispatch_size {dispatch_size} exceeds thread count {}",
+            self.num_threads
+        );
+        // nothing below panics while holding it, so poisoning can only come from elsewhere and is harmless
+        let dispatch = self.dispatch_lock.lock().unwrap_or_else(|e| e.into_inner());
+        while self.threads_active.load(Ordering::Acquire) != 0 {
+            std::hint::spin_loop();
+        }
+
+        *self.current_task.lock().unwrap_or_else(|e| e.into_inner()) = Some(task);
 
         // store how many threads will be working on the thing
         self.threads_active.store(dispatch_size as i32, Ordering::Relaxed);
@@ -93,20 +151,19 @@ impl Multiprocessor {
         // set N threads to do work for workgroup_size of N
         for i in 0..dispatch_size {
             let flag = &self.thread_flags[i];
-            flag.store(true, Ordering::Relaxed);
+            flag.store(true, Ordering::Release);
         }
 
-        // while self.threads_active.load(Ordering::Relaxed) != 0 {}
-        loop {
-            let active = self.threads_active.load(Ordering::Relaxed);
-            assert!(active >= 0, "threads_active should never be negative");
-            assert!(
-                active <= dispatch_size as i32,
-                "threads_active should never be greater than num_threads"
-            );
-            if active == 0 {
-                break;
-            }
+        // no asserts in here: panicking would unwind past workers that may still run the task
+        while self.threads_active.load(Ordering::Acquire) != 0 {
+            std::hint::spin_loop();
+        }
+        // workers dropped their clones before signalling, this drops the last one
+        *self.current_task.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let payload = self.task_panic.lock().unwrap_or_else(|e| e.into_inner()).take();
+        drop(dispatch);
+        if let Some(payload) = payload {
+            panic::resume_unwind(payload);
         }
     }
 
@@ -278,6 +335,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scoped_dispatch_borrows() {
+        let pool = Multiprocessor::new();
+        let dispatch_size = pool.used_thread_count().max(1);
+        let hits: Vec<AtomicUsize> = (0..dispatch_size).map(|_| AtomicUsize::new(0)).collect();
+
+        pool.dispatch_scoped(dispatch_size, |thread_id| {
+            hits[thread_id].fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert!(hits.iter().all(|h| h.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn test_concurrent_scoped_dispatches() {
+        // explicit thread count, so workers really run even on a single core machine
+        let pool = Multiprocessor::with_threads(3);
+        std::thread::scope(|scope| {
+            for caller in 0..2usize {
+                let pool = &pool;
+                scope.spawn(move || {
+                    for round in 0..20 {
+                        let expected = caller * 1000 + round;
+                        let hits: Vec<AtomicUsize> = (0..3).map(|_| AtomicUsize::new(0)).collect();
+                        pool.dispatch_scoped(3, |thread_id| {
+                            hits[thread_id].fetch_add(expected, Ordering::Relaxed);
+                        });
+                        assert!(hits.iter().all(|h| h.load(Ordering::Relaxed) == expected));
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_panicking_task_is_resumed() {
+        let pool = Multiprocessor::with_threads(2);
+        let counter = AtomicUsize::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.dispatch_scoped(2, |thread_id| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                if thread_id == 1 {
+                    panic!("task failed");
+                }
+            });
+        }));
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"task failed"));
+        assert_eq!(counter.load(Ordering::Relaxed), 2);
+
+        // the pool is not stuck, and the panicking worker is still alive
+        pool.dispatch_scoped(2, |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(counter.load(Ordering::Relaxed), 4);
+    }
+
     // TODO: this test fails when multiple tests run in parallel and interfere with each other causing slowdown
     // global test mutex? --test-threads=1 ?
 
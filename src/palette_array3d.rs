@@ -0,0 +1,271 @@
+// Palette-compressed 3D array: distinct values are stored once, cells hold bit-packed palette indices
+// Bits per index grow with the palette, so an array of few distinct values takes a few bits per cell.
+
+use crate::array3d::{Array3D, Dim3, Storage, ToUsize3};
+use crate::array3d_iter::Positions;
+use crate::bit_array::{get_bits, set_bits, words_for};
+use crate::layout::Layout;
+use qvek::vek::Vec3;
+use std::ops::Index;
+
+/// 3D array of palette indices packed into `u64` words (x-fastest, like `BitArray3d`).
+#[derive(Debug, Clone)]
+pub struct PaletteArray3D<T, D: Dim3> {
+    palette: Vec<T>,
+    indices: Box<[u64]>,
+    bits: usize,
+    pub dims: D,
+}
+
+/// Bits needed to store indices into a palette of `len` entries.
+fn bits_for(len: usize) -> usize {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()) as usize
+}
+
+impl<T, D: Dim3> PaletteArray3D<T, D> {
+    /// Creates a new array with all elements set to `value`. Takes no index storage until a second value is set.
+    pub fn new_filled(dims: D, value: T) -> Self {
+        Self {
+            palette: vec![value],
+            indices: Box::new([]),
+            bits: 0,
+            dims,
+        }
+    }
+
+    /// Returns the dimensions.
+    pub fn dimensions(&self) -> Vec3<usize> {
+        self.dims.xyz()
+    }
+
+    /// Returns `true` if (x, y, z) is inside the array.
+    pub fn in_bounds(&self, x: usize, y: usize, z: usize) -> bool {
+        x < self.dims.x() && y < self.dims.y() && z < self.dims.z()
+    }
+
+    /// Distinct values, possibly including ones no cell uses anymore (see `compact`).
+    pub fn palette(&self) -> &[T] {
+        &self.palette
+    }
+
+    /// Bits each cell takes in index storage.
+    pub fn bits_per_entry(&self) -> usize {
+        self.bits
+    }
+
+    fn linear_index(&self, x: usize, y: usize, z: usize) -> usize {
+        assert!(
+            self.in_bounds(x, y, z),
+            "Index out of bounds x: {x} y: {y} z: {z}, dims: {:?}",
+            self.dimensions()
+        );
+        x + y * self.dims.x() + z * self.dims.x() * self.dims.y()
+    }
+
+    fn palette_index(&self, cell: usize) -> usize {
+        get_bits(&self.indices, cell * self.bits, self.bits)
+    }
+
+    /// Shared reference at (x, y, z).
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &T {
+        let cell = self.linear_index(x, y, z);
+        &self.palette[self.palette_index(cell)]
+    }
+
+    /// Shared reference at (x, y, z), `None` if out of bounds.
+    pub fn try_get(&self, x: usize, y: usize, z: usize) -> Option<&T> {
+        self.in_bounds(x, y, z).then(|| self.get(x, y, z))
+    }
+
+    /// Positions of every element, x-fastest.
+    pub fn positions(&self) -> Positions {
+        Positions::new(Vec3::zero(), self.dimensions())
+    }
+
+    /// Iterator over all elements, x-fastest.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &T> {
+        (0..self.dims.total_len()).map(|cell| &self.palette[self.palette_index(cell)])
+    }
+
+    /// Iterates (`position`, `&T`) of every element, x-fastest.
+    pub fn indexed_iter(&self) -> impl ExactSizeIterator<Item = (Vec3<usize>, &T)> {
+        self.positions().zip(self.iter())
+    }
+
+    /// Re-packs every cell with `bits` per entry, `remap` translates old palette indices.
+    fn repack(&mut self, bits: usize, remap: impl Fn(usize) -> usize) {
+        let len = self.dims.total_len();
+        let mut indices = vec![0; words_for::<u64>(len * bits)].into_boxed_slice();
+        for cell in 0..len {
+            set_bits(
+                &mut indices,
+                cell * bits,
+                bits,
+                remap(self.palette_index(cell)),
+            );
+        }
+        self.indices = indices;
+        self.bits = bits;
+    }
+}
+
+impl<T: PartialEq, D: Dim3> PaletteArray3D<T, D> {
+    /// Palette index of `value`, added to the palette (widening storage if needed) if missing.
+    fn intern(&mut self, value: T) -> usize {
+        if let Some(index) = self.palette.iter().position(|entry| *entry == value) {
+            return index;
+        }
+        self.palette.push(value);
+        let bits = bits_for(self.palette.len());
+        if bits > self.bits {
+            self.repack(bits, |index| index);
+        }
+        self.palette.len() - 1
+    }
+
+    /// Sets value at (x, y, z).
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: T) {
+        let cell = self.linear_index(x, y, z);
+        let index = self.intern(value);
+        set_bits(&mut self.indices, cell * self.bits, self.bits, index);
+    }
+
+    /// Fills every element with `value`, dropping the rest of the palette.
+    pub fn fill(&mut self, value: T) {
+        *self = Self::new_filled(self.dims, value);
+    }
+
+    /// Drops palette entries no cell uses and narrows index storage to fit.
+    pub fn compact(&mut self) {
+        let mut used = vec![false; self.palette.len()];
+        for cell in 0..self.dims.total_len() {
+            used[self.palette_index(cell)] = true;
+        }
+        // new index of every kept entry, in the old order
+        let remap: Vec<usize> = used
+            .iter()
+            .scan(0, |next, &keep| {
+                let index = *next;
+                *next += keep as usize;
+                Some(index)
+            })
+            .collect();
+        let mut keep = used.iter();
+        self.palette.retain(|_| *keep.next().unwrap());
+        if self.palette.is_empty() {
+            // array has no cells, nothing references the palette
+            return;
+        }
+        self.repack(bits_for(self.palette.len()), |index| remap[index]);
+    }
+
+    /// Compresses `array`.
+    pub fn from_array3d<L: Layout, S: Storage<T>>(array: &Array3D<T, D, L, S>) -> Self
+    where
+        T: Clone,
+    {
+        let mut cells = array.indexed_iter();
+        let Some((_, first)) = cells.next() else {
+            return Self {
+                palette: Vec::new(),
+                indices: Box::new([]),
+                bits: 0,
+                dims: array.dims,
+            };
+        };
+        let mut palette = Self::new_filled(array.dims, first.clone());
+        for (pos, value) in cells {
+            palette.set(pos.x, pos.y, pos.z, value.clone());
+        }
+        palette
+    }
+}
+
+impl<T: Clone, D: Dim3> PaletteArray3D<T, D> {
+    /// Decompresses into a linear `Array3D`.
+    pub fn to_array3d(&self) -> Array3D<T, D> {
+        let data: Vec<T> = self.iter().cloned().collect();
+        Array3D::from_storage(self.dims, data.into_boxed_slice())
+    }
+}
+
+impl<T, D: Dim3, I: ToUsize3> Index<I> for PaletteArray3D<T, D> {
+    type Output = T;
+    fn index(&self, index: I) -> &Self::Output {
+        let (x, y, z) = index.to_usize3();
+        self.get(x, y, z)
+    }
+}
+
+/// Compares dims and values, palettes may differ in order and unused entries.
+impl<T: PartialEq, D: Dim3> PartialEq for PaletteArray3D<T, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.dimensions() == other.dimensions() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, D: Dim3> Eq for PaletteArray3D<T, D> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array3d::{RuntimeDims, numbered};
+    use crate::layout::Morton;
+
+    #[test]
+    fn test_palette_widening() {
+        let mut array = PaletteArray3D::new_filled(RuntimeDims::new(5, 4, 3), 0u16);
+        assert_eq!(array.bits_per_entry(), 0);
+        assert_eq!(array[(4, 3, 2)], 0);
+
+        array.set(1, 2, 1, 500);
+        assert_eq!(array.bits_per_entry(), 1);
+        array.set(0, 0, 0, 7);
+        array.set(4, 3, 2, 9);
+        assert_eq!(array.bits_per_entry(), 2);
+        array.set(2, 2, 2, 1000);
+        assert_eq!(array.bits_per_entry(), 3);
+        // values written before widening survive it
+        assert_eq!(*array.get(1, 2, 1), 500);
+        assert_eq!(*array.get(0, 0, 0), 7);
+        assert_eq!(*array.get(4, 3, 2), 9);
+        assert_eq!(*array.get(2, 2, 2), 1000);
+        assert_eq!(*array.get(3, 1, 0), 0);
+        assert_eq!(array.try_get(5, 0, 0), None);
+        assert_eq!(array.iter().filter(|&&v| v == 0).count(), 60 - 4);
+
+        array.set(0, 0, 0, 0);
+        array.set(4, 3, 2, 0);
+        assert_eq!(array.palette().len(), 5);
+        array.compact();
+        assert_eq!(array.palette(), [0, 500, 1000]);
+        assert_eq!(array.bits_per_entry(), 2);
+        assert_eq!(*array.get(2, 2, 2), 1000);
+        assert_eq!(*array.get(1, 2, 1), 500);
+    }
+
+    #[test]
+    fn test_array3d_round_trip() {
+        // (x + 2y) % 4, four distinct values
+        let dense = numbered(RuntimeDims::new(3, 5, 2), Morton).map(|v| (v % 4) as u8);
+        let packed = PaletteArray3D::from_array3d(&dense);
+        assert_eq!(packed.palette().len(), 4);
+        assert!(packed.indexed_iter().all(|(pos, v)| v == &dense[(pos.x, pos.y, pos.z)]));
+        let unpacked = packed.to_array3d();
+        assert!(unpacked.indexed_iter().all(|(pos, v)| v == &dense[(pos.x, pos.y, pos.z)]));
+
+        let mut other = PaletteArray3D::new_filled(RuntimeDims::new(3, 5, 2), 3u8);
+        for (pos, &value) in dense.indexed_iter() {
+            other.set(pos.x, pos.y, pos.z, value);
+        }
+        assert_ne!(other.palette(), packed.palette());
+        assert_eq!(other, packed);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds_panics() {
+        let array = PaletteArray3D::new_filled(RuntimeDims::new(2, 2, 2), 1);
+        array.get(0, 2, 0);
+    }
+}
@@ -3,7 +3,7 @@
 
 use std::ops::{Index, IndexMut};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Ring<T> {
     pub data: Box<[T]>,
     pub index: usize,
@@ -0,0 +1,253 @@
+// Boundary handling for neighbour lookups: any signed coordinate is mapped into the array by an `EdgeMode`
+// Samplers borrow the array, so reads at chunk edges do not need per-call bounds juggling.
+
+use crate::array2d::DArray2D;
+use crate::array3d::{Array3D, Dim3, Storage};
+use crate::layout::Layout;
+use qvek::vek::{Vec2, Vec3};
+use std::ops::{Add, Mul};
+
+/// What to read when a coordinate falls outside the array.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EdgeMode<T> {
+    /// Nearest edge element (`-3 -> 0`, `size + 3 -> size - 1`).
+    #[default]
+    Clamp,
+    /// Periodic, the array tiles space (`-1 -> size - 1`, `size -> 0`).
+    Wrap,
+    /// Reflected at the edges, edge element repeated (`-1 -> 0`, `size -> size - 1`).
+    Mirror,
+    /// Fixed value for everything outside.
+    Constant(T),
+}
+
+impl<T> EdgeMode<T> {
+    /// Maps `coord` onto `0..size`, `None` for `Constant` (or an empty axis).
+    pub fn resolve(&self, coord: i32, size: usize) -> Option<usize> {
+        if let Ok(c) = usize::try_from(coord)
+            && c < size
+        {
+            return Some(c);
+        }
+        if size == 0 {
+            return None;
+        }
+        let c = coord as i64;
+        let n = size as i64;
+        let resolved = match self {
+            EdgeMode::Clamp => c.clamp(0, n - 1),
+            EdgeMode::Wrap => c.rem_euclid(n),
+            EdgeMode::Mirror => {
+                let m = c.rem_euclid(2 * n);
+                if m < n { m } else { 2 * n - 1 - m }
+            }
+            EdgeMode::Constant(_) => return None,
+        };
+        Some(resolved as usize)
+    }
+
+    /// Value to return when `resolve` gave up.
+    pub(crate) fn fallback(&self) -> &T {
+        match self {
+            EdgeMode::Constant(value) => value,
+            _ => panic!("cannot sample an empty array"),
+        }
+    }
+}
+
+/// Reads an `Array3D` at any signed coordinate, see `Array3D::sampler`.
+pub struct Sampler3D<'a, T, D: Dim3, L: Layout, S = Box<[T]>> {
+    array: &'a Array3D<T, D, L, S>,
+    mode: EdgeMode<T>,
+}
+
+// only the reference and the mode are copied, so storage needs no bounds
+impl<'a, T: Clone, D: Dim3, L: Layout, S> Clone for Sampler3D<'a, T, D, L, S> {
+    fn clone(&self) -> Self {
+        Self {
+            array: self.array,
+            mode: self.mode.clone(),
+        }
+    }
+}
+
+impl<'a, T: Copy, D: Dim3, L: Layout, S> Copy for Sampler3D<'a, T, D, L, S> {}
+
+impl<'a, T, D: Dim3, L: Layout, S: Storage<T>> Sampler3D<'a, T, D, L, S> {
+    pub fn mode(&self) -> &EdgeMode<T> {
+        &self.mode
+    }
+
+    pub fn array(&self) -> &'a Array3D<T, D, L, S> {
+        self.array
+    }
+
+    /// In-bounds position `pos` maps to, `None` if it reads the constant.
+    pub fn resolve(&self, pos: Vec3<i32>) -> Option<Vec3<usize>> {
+        let dims = self.array.dimensions();
+        Some(Vec3::new(
+            self.mode.resolve(pos.x, dims.x)?,
+            self.mode.resolve(pos.y, dims.y)?,
+            self.mode.resolve(pos.z, dims.z)?,
+        ))
+    }
+
+    /// Element at `pos`, resolved by the edge mode.
+    pub fn get(&self, pos: Vec3<i32>) -> &T {
+        match self.resolve(pos) {
+            Some(p) => self.array.get(p.x, p.y, p.z),
+            None => self.mode.fallback(),
+        }
+    }
+
+    /// Element closest to `pos`. Elements sit at integer coordinates, halves round up.
+    pub fn sample_nearest(&self, pos: Vec3<f32>) -> &T {
+        let rounded = pos.map(|c| (c + 0.5).floor() as i32);
+        self.get(rounded)
+    }
+
+    /// Trilinear blend of the 8 elements around `pos`, outside ones resolved by the edge mode.
+    pub fn sample_trilinear(&self, pos: Vec3<f32>) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+    {
+        let floor = pos.map(f32::floor);
+        let t = pos - floor;
+        // `as` saturates, keep room for the +1 neighbour (that far out every tap is past the edge anyway)
+        let base = floor.map(|c| (c as i32).min(i32::MAX - 1));
+        let lerp = |a: T, b: T, t: f32| a * (1.0 - t) + b * t;
+        let at = |x, y, z| *self.get(base + Vec3::new(x, y, z));
+        let lerp_x = |y, z| lerp(at(0, y, z), at(1, y, z), t.x);
+        let lerp_y = |z| lerp(lerp_x(0, z), lerp_x(1, z), t.y);
+        lerp(lerp_y(0), lerp_y(1), t.z)
+    }
+}
+
+/// Reads a `DArray2D` at any signed coordinate, see `DArray2D::sampler`.
+#[derive(Clone, Copy)]
+pub struct Sampler2D<'a, T> {
+    array: &'a DArray2D<T>,
+    mode: EdgeMode<T>,
+}
+
+impl<'a, T> Sampler2D<'a, T> {
+    pub fn mode(&self) -> &EdgeMode<T> {
+        &self.mode
+    }
+
+    pub fn array(&self) -> &'a DArray2D<T> {
+        self.array
+    }
+
+    /// In-bounds position `pos` maps to, `None` if it reads the constant.
+    pub fn resolve(&self, pos: Vec2<i32>) -> Option<Vec2<usize>> {
+        Some(Vec2::new(
+            self.mode.resolve(pos.x, self.array.x_size)?,
+            self.mode.resolve(pos.y, self.array.y_size)?,
+        ))
+    }
+
+    /// Element at `pos`, resolved by the edge mode.
+    pub fn get(&self, pos: Vec2<i32>) -> &T {
+        match self.resolve(pos) {
+            Some(p) => self.array.get_ref(p.x, p.y),
+            None => self.mode.fallback(),
+        }
+    }
+}
+
+// extend behaviour of arrays to support edge-handled reads
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// Sampler reading any signed coordinate, outside ones handled by `mode`.
+    pub fn sampler(&self, mode: EdgeMode<T>) -> Sampler3D<'_, T, D, L, S> {
+        Sampler3D { array: self, mode }
+    }
+}
+
+impl<T> DArray2D<T> {
+    /// Sampler reading any signed coordinate, outside ones handled by `mode`.
+    pub fn sampler(&self, mode: EdgeMode<T>) -> Sampler2D<'_, T> {
+        Sampler2D { array: self, mode }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array3d::{RuntimeDims, number};
+
+    #[test]
+    fn test_resolve() {
+        let size = 4;
+        let coords = [-5, -4, -1, 0, 3, 4, 7, 8];
+        let resolve = |mode: EdgeMode<()>| -> Vec<_> {
+            coords.iter().map(|&c| mode.resolve(c, size)).collect()
+        };
+        assert_eq!(resolve(EdgeMode::Clamp), [0, 0, 0, 0, 3, 3, 3, 3].map(Some));
+        assert_eq!(resolve(EdgeMode::Wrap), [3, 0, 3, 0, 3, 0, 3, 0].map(Some));
+        assert_eq!(
+            resolve(EdgeMode::Mirror),
+            [3, 3, 0, 0, 3, 3, 0, 0].map(Some)
+        );
+        assert_eq!(
+            resolve(EdgeMode::Constant(())),
+            [None, None, None, Some(0), Some(3), None, None, None]
+        );
+        assert_eq!(EdgeMode::<()>::Wrap.resolve(i32::MIN, 3), Some(1));
+    }
+
+    #[test]
+    fn test_samplers() {
+        let array = Array3D::from_fn_pos(RuntimeDims::new(2, 2, 2), |p| number(p) as i32);
+        let clamp = array.sampler(EdgeMode::Clamp);
+        assert_eq!(*clamp.get(Vec3::new(-1, 5, 1)), 110);
+        let wrap = array.sampler(EdgeMode::Wrap);
+        assert_eq!(*wrap.get(Vec3::new(-1, 2, 3)), 101);
+        let constant = array.sampler(EdgeMode::Constant(-1));
+        // samplers of boxed arrays are `Copy`
+        let copy = constant;
+        assert_eq!(*copy.get(Vec3::new(1, 1, 1)), 111);
+        assert_eq!(*constant.get(Vec3::new(1, 1, 2)), -1);
+
+        assert_eq!(*constant.sample_nearest(Vec3::new(0.6, 0.4, 1.4)), 101);
+        assert_eq!(*constant.sample_nearest(Vec3::new(-0.6, 0.0, 0.0)), -1);
+
+        let mut plane = DArray2D::new(3, 2);
+        plane[(2, 1)] = 9;
+        assert_eq!(*plane.sampler(EdgeMode::Mirror).get(Vec2::new(3, -2)), 9);
+        assert_eq!(
+            *plane.sampler(EdgeMode::Constant(4)).get(Vec2::new(3, 0)),
+            4
+        );
+    }
+
+    #[test]
+    fn test_trilinear() {
+        let array = Array3D::from_fn_pos(RuntimeDims::new(2, 2, 2), |p| number(p) as f32);
+        let clamp = array.sampler(EdgeMode::Clamp);
+        // linear field is reproduced exactly inside the array
+        assert_eq!(clamp.sample_trilinear(Vec3::new(0.5, 0.25, 0.75)), 78.0);
+        assert_eq!(clamp.sample_trilinear(Vec3::new(1.0, 1.0, 1.0)), 111.0);
+        // and flattened past the clamped edge
+        assert_eq!(clamp.sample_trilinear(Vec3::new(1.5, -3.0, 0.0)), 1.0);
+
+        let constant = array.sampler(EdgeMode::Constant(0.0));
+        assert_eq!(constant.sample_trilinear(Vec3::new(-0.5, 0.0, 0.0)), 0.0);
+        assert_eq!(constant.sample_trilinear(Vec3::new(1.5, 0.0, 0.0)), 0.5);
+
+        let vectors = Array3D::new_filled(RuntimeDims::new(1, 1, 1), Vec3::new(1.0f32, 2.0, 3.0));
+        let wrap = vectors.sampler(EdgeMode::Wrap);
+        assert_eq!(
+            wrap.sample_trilinear(Vec3::new(0.3, 7.1, -2.9)),
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+
+        // far-away coordinates saturate instead of overflowing
+        assert_eq!(clamp.sample_trilinear(Vec3::new(3e9, 0.0, 0.0)), 1.0);
+        assert_eq!(
+            clamp.sample_trilinear(Vec3::new(-3e9, 1e30, f32::MAX)),
+            110.0
+        );
+        assert_eq!(*clamp.sample_nearest(Vec3::new(3e9, -3e9, 0.0)), 1.0);
+    }
+}
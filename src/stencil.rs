@@ -0,0 +1,243 @@
+// Neighbourhood passes over arrays: convolution, box blur and morphology
+// Reads outside the array are resolved by an `EdgeMode`, outputs have the same dims (and layout) as the input.
+
+use crate::array3d::*;
+use crate::bit_array::{BitArray3d, BitWord};
+use crate::layout::Layout;
+use crate::sampler::{EdgeMode, Sampler3D};
+use qvek::vek::Vec3;
+use std::ops::{Add, Mul};
+
+const AXES: [Vec3<i32>; 3] = [
+    Vec3 { x: 1, y: 0, z: 0 },
+    Vec3 { x: 0, y: 1, z: 0 },
+    Vec3 { x: 0, y: 0, z: 1 },
+];
+
+fn signed(pos: Vec3<usize>) -> Vec3<i32> {
+    pos.map(|c| c as i32)
+}
+
+/// Sum of `values` scaled by `weights`. There must be at least one.
+fn weighted_sum<T>(mut terms: impl Iterator<Item = (T, f32)>) -> T
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    let (first, weight) = terms.next().expect("kernel must not be empty");
+    terms.fold(first * weight, |acc, (value, weight)| acc + value * weight)
+}
+
+// extend behaviour of array to support stencil passes
+impl<T, D: Dim3, L: Layout, S: Storage<T>> Array3D<T, D, L, S> {
+    /// New array where every element is `f(sampler, position)`, neighbours are read through the sampler.
+    /// Result is in the default (linear) layout.
+    pub fn stencil<U, F>(&self, mode: EdgeMode<T>, f: F) -> Array3D<U, D>
+    where
+        F: Fn(&Sampler3D<'_, T, D, L, S>, Vec3<i32>) -> U,
+    {
+        let sampler = self.sampler(mode);
+        // linear storage is x-fastest, same as positions
+        let data: Vec<U> = self.positions().map(|pos| f(&sampler, signed(pos))).collect();
+        Array3D::from_storage(self.dims, data.into_boxed_slice())
+    }
+}
+
+impl<T: Clone, D: Dim3, L: Layout, S: Storage<T> + Clone> Array3D<T, D, L, S> {
+    /// Copy of the array with every element replaced by `f(sampler, position)`.
+    fn stencil_in_place<F>(&self, mode: EdgeMode<T>, f: F) -> Self
+    where
+        F: Fn(&Sampler3D<'_, T, D, L, S>, Vec3<i32>) -> T,
+    {
+        let sampler = self.sampler(mode);
+        // start from a copy, so layout padding has something in it
+        let mut data = self.data.clone();
+        for pos in self.positions() {
+            data[L::index(&self.dims, pos.x, pos.y, pos.z)] = f(&sampler, signed(pos));
+        }
+        Self::from_storage(self.dims, data)
+    }
+
+    /// Morphology along each axis in turn, `pick` chooses between two neighbours.
+    fn morphology(&self, radius: usize, mode: EdgeMode<T>, pick: impl Fn(T, T) -> T) -> Self {
+        let radius = radius as i32;
+        let mut result: Option<Self> = None;
+        for axis in AXES {
+            let source = result.as_ref().unwrap_or(self);
+            let pass = source.stencil_in_place(mode.clone(), |sampler, pos| {
+                (-radius..=radius)
+                    .map(|o| sampler.get(pos + axis * o).clone())
+                    .reduce(&pick)
+                    .unwrap()
+            });
+            result = Some(pass);
+        }
+        result.unwrap()
+    }
+}
+
+impl<T: PartialOrd + Clone, D: Dim3, L: Layout, S: Storage<T> + Clone> Array3D<T, D, L, S> {
+    /// Maximum over the `(2 * radius + 1)`³ cube around every element.
+    pub fn dilate(&self, radius: usize, mode: EdgeMode<T>) -> Self {
+        self.morphology(radius, mode, |a, b| if b > a { b } else { a })
+    }
+
+    /// Minimum over the `(2 * radius + 1)`³ cube around every element.
+    pub fn erode(&self, radius: usize, mode: EdgeMode<T>) -> Self {
+        self.morphology(radius, mode, |a, b| if b < a { b } else { a })
+    }
+}
+
+impl<T, D: Dim3, L: Layout, S: Storage<T> + Clone> Array3D<T, D, L, S>
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    /// Convolves with an `N`³ kernel indexed `[z][y][x]`, centered on the element. `N` must be odd.
+    pub fn convolve<const N: usize>(&self, kernel: &[[[f32; N]; N]; N], mode: EdgeMode<T>) -> Self {
+        const { assert!(N % 2 == 1, "kernel size must be odd") };
+        let half = (N / 2) as i32;
+        self.stencil_in_place(mode, |sampler, pos| {
+            let taps = (0..N * N * N).map(|i| {
+                let (x, y, z) = (i % N, i / N % N, i / (N * N));
+                let offset = Vec3::new(x as i32, y as i32, z as i32) - half;
+                (*sampler.get(pos + offset), kernel[z][y][x])
+            });
+            weighted_sum(taps)
+        })
+    }
+
+    /// Convolves with `kernel` along x, then y, then z. Same as `convolve` with the outer product
+    /// of `kernel` with itself (except near edges with `Constant`), at `3 * N` instead of `N`³ reads
+    /// per element. `N` must be odd.
+    pub fn convolve_separable<const N: usize>(&self, kernel: &[f32; N], mode: EdgeMode<T>) -> Self {
+        const { assert!(N % 2 == 1, "kernel size must be odd") };
+        self.separable(kernel, mode)
+    }
+
+    /// Average over the `(2 * radius + 1)`³ cube around every element.
+    pub fn box_blur(&self, radius: usize, mode: EdgeMode<T>) -> Self {
+        let taps = 2 * radius + 1;
+        self.separable(&vec![1.0 / taps as f32; taps], mode)
+    }
+
+    fn separable(&self, kernel: &[f32], mode: EdgeMode<T>) -> Self {
+        let half = (kernel.len() / 2) as i32;
+        let mut result: Option<Self> = None;
+        for axis in AXES {
+            let source = result.as_ref().unwrap_or(self);
+            let pass = source.stencil_in_place(mode, |sampler, pos| {
+                let taps = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &weight)| (*sampler.get(pos + axis * (i as i32 - half)), weight));
+                weighted_sum(taps)
+            });
+            result = Some(pass);
+        }
+        result.unwrap()
+    }
+}
+
+impl<T, D> BitArray3d<T, D>
+where
+    D: Dim3,
+    T: BitWord,
+{
+    /// Bit at signed `pos`, outside resolved by `mode`.
+    fn get_edged(&self, pos: Vec3<i32>, mode: EdgeMode<bool>) -> bool {
+        let dims = self.dims.xyz();
+        match (
+            mode.resolve(pos.x, dims.x),
+            mode.resolve(pos.y, dims.y),
+            mode.resolve(pos.z, dims.z),
+        ) {
+            (Some(x), Some(y), Some(z)) => self.get(x, y, z),
+            _ => *mode.fallback(),
+        }
+    }
+
+    /// Along each axis in turn, a bit becomes `wins` if any neighbour within `radius` is.
+    fn morphology(&self, radius: usize, mode: EdgeMode<bool>, wins: bool) -> Self {
+        let radius = radius as i32;
+        let dims = self.dims.xyz();
+        let mut result: Option<Self> = None;
+        for axis in AXES {
+            let source = result.as_ref().unwrap_or(self);
+            let mut pass = Self::new(self.dims);
+            for z in 0..dims.z {
+                for y in 0..dims.y {
+                    for x in 0..dims.x {
+                        let pos = signed(Vec3::new(x, y, z));
+                        let hit = (-radius..=radius)
+                            .any(|o| source.get_edged(pos + axis * o, mode) == wins);
+                        pass.set(x, y, z, hit == wins);
+                    }
+                }
+            }
+            result = Some(pass);
+        }
+        result.unwrap()
+    }
+
+    /// Sets every bit that has a set bit within the `(2 * radius + 1)`³ cube around it.
+    pub fn dilate(&self, radius: usize, mode: EdgeMode<bool>) -> Self {
+        self.morphology(radius, mode, true)
+    }
+
+    /// Clears every bit that has a cleared bit within the `(2 * radius + 1)`³ cube around it.
+    pub fn erode(&self, radius: usize, mode: EdgeMode<bool>) -> Self {
+        self.morphology(radius, mode, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Morton;
+
+    #[test]
+    fn test_convolve() {
+        let array = numbered(RuntimeDims::new(4, 3, 5), Morton).map(|v| v as f32);
+        let mut identity = [[[0.0; 3]; 3]; 3];
+        identity[1][1][1] = 1.0;
+        let same = array.convolve(&identity, EdgeMode::Clamp);
+        assert!(same.indexed_iter().all(|(pos, v)| v == &array[(pos.x, pos.y, pos.z)]));
+
+        // shift by one along x: reads the left neighbour
+        let mut shift = [[[0.0; 3]; 3]; 3];
+        shift[1][1][0] = 1.0;
+        let shifted = array.convolve(&shift, EdgeMode::Constant(-1.0));
+        assert_eq!(shifted[(2, 1, 1)], 111.0);
+        assert_eq!(shifted[(0, 1, 1)], -1.0);
+
+        // linear field is kept by a symmetric blur away from the edges
+        let blurred = array.box_blur(1, EdgeMode::Clamp);
+        assert!((blurred[(1, 1, 2)] - 211.0).abs() < 1e-3);
+        let full = array.convolve(&[[[1.0 / 27.0; 3]; 3]; 3], EdgeMode::Mirror);
+        let separable = array.convolve_separable(&[1.0 / 3.0; 3], EdgeMode::Mirror);
+        assert!(
+            full.indexed_iter().all(|(pos, v)| (v - separable[(pos.x, pos.y, pos.z)]).abs() < 1e-3)
+        );
+    }
+
+    #[test]
+    fn test_morphology() {
+        let mut array = Array3D::new_filled(RuntimeDims::new(5, 5, 5), 0u8);
+        array[(2, 2, 2)] = 9;
+        let dilated = array.dilate(1, EdgeMode::Clamp);
+        assert_eq!(dilated.iter().filter(|&&v| v == 9).count(), 27);
+        assert_eq!(dilated[(1, 3, 1)], 9);
+        assert_eq!(dilated[(0, 2, 2)], 0);
+        let eroded = dilated.erode(1, EdgeMode::Clamp);
+        assert!(eroded.iter().eq(array.iter()));
+
+        let mut bits = BitArray3d::<u32, _>::new(RuntimeDims::new(4, 4, 4));
+        bits.set(0, 0, 0, true);
+        let dilated = bits.dilate(1, EdgeMode::Constant(false));
+        assert!(dilated.get(1, 1, 1));
+        assert!(!dilated.get(2, 0, 0));
+        let eroded = dilated.erode(1, EdgeMode::Clamp);
+        assert!(eroded.get(0, 0, 0));
+        assert!(!eroded.get(1, 1, 1));
+        assert!(!dilated.erode(1, EdgeMode::Constant(false)).get(0, 0, 0));
+    }
+}